@@ -1,8 +1,82 @@
+use std::path::PathBuf;
+
 use crate::{Error, Result};
 
+/// The default maximum depth of a mapping, like Elasticsearch's `index.mapping.depth.limit`.
+pub const DEFAULT_MAX_DEPTH: usize = 20;
+
 #[derive(Debug)]
 pub struct Config {
     pub target: Option<String>,
+    pub output: Output,
+    /// Write the message fields of each document as component templates of their own.
+    pub split_components: bool,
+    pub max_depth: usize,
+    /// The inferred mapping type of `google.protobuf.Timestamp` fields.
+    pub timestamp: TimestampType,
+    /// The inferred mapping type of `google.protobuf.Duration` fields.
+    pub duration: DurationType,
+    pub diagnostics: DiagnosticsFormat,
+    /// Write diagnostics to this file instead of standard error.
+    pub diagnostics_path: Option<PathBuf>,
+    /// Directories to read source files from when quoting them in diagnostics.
+    pub include_paths: Vec<PathBuf>,
+    /// Report all warnings as errors.
+    pub werror: bool,
+    /// Warning codes to suppress, e.g., `W001`.
+    pub allow: Vec<String>,
+    /// Warning codes to report as errors.
+    pub deny: Vec<String>,
+}
+
+/// The kind of document the plugin writes for each message.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Output {
+    /// The document mapping only.
+    #[default]
+    Mapping,
+    /// A create index request body with `settings`, `mappings`, and `aliases`.
+    Index,
+    /// A composable index template body.
+    IndexTemplate,
+    /// A component template body.
+    ComponentTemplate,
+    /// A legacy index template body.
+    LegacyTemplate,
+}
+
+/// The mapping type the plugin infers for `google.protobuf.Timestamp` fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TimestampType {
+    /// Millisecond precision.
+    #[default]
+    Date,
+    /// Nanosecond precision.
+    DateNanos,
+}
+
+/// The mapping type the plugin infers for `google.protobuf.Duration` fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DurationType {
+    /// The canonical JSON representation, e.g., `"1.5s"`.
+    #[default]
+    Keyword,
+    /// An integer count of some unit, e.g., milliseconds.
+    Long,
+}
+
+/// The format of the diagnostics the plugin reports.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DiagnosticsFormat {
+    /// One line per diagnostic.
+    #[default]
+    Human,
+    /// Diagnostics that quote the source, with a hint about how to fix the problem.
+    Rich,
+    /// A JSON array of diagnostics.
+    Json,
+    /// A [SARIF](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) log.
+    Sarif,
 }
 
 impl TryFrom<&str> for Config {
@@ -10,20 +84,169 @@ impl TryFrom<&str> for Config {
 
     fn try_from(s: &str) -> Result<Self> {
         let mut target = None;
+        let mut output = Output::default();
+        let mut split_components = false;
+        let mut max_depth = DEFAULT_MAX_DEPTH;
+        let mut timestamp = TimestampType::default();
+        let mut duration = DurationType::default();
+        let mut diagnostics = DiagnosticsFormat::default();
+        let mut diagnostics_path = None;
+        let mut include_paths = Vec::new();
+        let mut werror = false;
+        let mut allow = Vec::new();
+        let mut deny = Vec::new();
         for param in s.split(',').filter(|s| !s.is_empty()) {
             if let Some(v) = param.strip_prefix("target=") {
                 target = Some(v.to_string());
+            } else if let Some(v) = param.strip_prefix("output=") {
+                output = Output::try_from(v)?;
+            } else if param == "split_components" {
+                split_components = true;
+            } else if let Some(v) = param.strip_prefix("max_depth=") {
+                max_depth = match v.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(Error::InvalidRequest(format!("invalid max_depth: {v}"))),
+                };
+            } else if let Some(v) = param.strip_prefix("timestamp=") {
+                timestamp = TimestampType::try_from(v)?;
+            } else if let Some(v) = param.strip_prefix("duration=") {
+                duration = DurationType::try_from(v)?;
+            } else if let Some(v) = param.strip_prefix("diagnostics=") {
+                diagnostics = DiagnosticsFormat::try_from(v)?;
+            } else if let Some(v) = param.strip_prefix("diagnostics_path=") {
+                if v.is_empty() {
+                    return Err(Error::InvalidRequest(
+                        "diagnostics_path cannot be empty".to_string(),
+                    ));
+                }
+                diagnostics_path = Some(PathBuf::from(v));
+            } else if let Some(v) = param.strip_prefix("include_path=") {
+                include_paths.push(PathBuf::from(v));
+            } else if param == "werror" {
+                werror = true;
+            } else if let Some(v) = param.strip_prefix("allow=") {
+                allow.push(warning_code(v)?);
+            } else if let Some(v) = param.strip_prefix("deny=") {
+                deny.push(warning_code(v)?);
             } else {
                 return Err(Error::InvalidRequest(format!("unknown parameter: {param}")));
             }
         }
-        Ok(Self { target })
+        Ok(Self {
+            target,
+            output,
+            split_components,
+            max_depth,
+            timestamp,
+            duration,
+            diagnostics,
+            diagnostics_path,
+            include_paths,
+            werror,
+            allow,
+            deny,
+        })
+    }
+}
+
+/// Check that `code` is a warning code, e.g., `W001`. Errors cannot be suppressed.
+fn warning_code(code: &str) -> Result<String> {
+    if is_warning_code(code) {
+        return Ok(code.to_string());
+    }
+    Err(Error::InvalidRequest(format!(
+        "invalid warning code: {code}"
+    )))
+}
+
+/// Return whether `code` is a warning code, e.g., `W001`.
+pub fn is_warning_code(code: &str) -> bool {
+    code.strip_prefix('W')
+        .is_some_and(|number| number.len() == 3 && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl TryFrom<&str> for TimestampType {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "date" => Ok(Self::Date),
+            "date_nanos" => Ok(Self::DateNanos),
+            _ => Err(Error::InvalidRequest(format!(
+                "unknown timestamp type: {s}"
+            ))),
+        }
+    }
+}
+
+impl TimestampType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Date => "date",
+            Self::DateNanos => "date_nanos",
+        }
+    }
+}
+
+impl TryFrom<&str> for DurationType {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "keyword" => Ok(Self::Keyword),
+            "long" => Ok(Self::Long),
+            _ => Err(Error::InvalidRequest(format!("unknown duration type: {s}"))),
+        }
+    }
+}
+
+impl DurationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Long => "long",
+        }
+    }
+}
+
+impl TryFrom<&str> for Output {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "mapping" => Ok(Self::Mapping),
+            "index" => Ok(Self::Index),
+            "index_template" => Ok(Self::IndexTemplate),
+            "component_template" => Ok(Self::ComponentTemplate),
+            "legacy_template" => Ok(Self::LegacyTemplate),
+            _ => Err(Error::InvalidRequest(format!("unknown output: {s}"))),
+        }
+    }
+}
+
+impl TryFrom<&str> for DiagnosticsFormat {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "human" => Ok(Self::Human),
+            "rich" => Ok(Self::Rich),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(Error::InvalidRequest(format!(
+                "unknown diagnostics format: {s}"
+            ))),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use std::path::Path;
+
+    use super::{
+        Config, DEFAULT_MAX_DEPTH, DiagnosticsFormat, DurationType, Output, TimestampType,
+    };
     use crate::Error;
 
     #[test]
@@ -36,6 +259,147 @@ mod tests {
     fn test_empty() {
         let config = Config::try_from("").unwrap();
         assert_eq!(config.target, None);
+        assert_eq!(config.output, Output::Mapping);
+        assert!(!config.split_components);
+        assert_eq!(config.max_depth, DEFAULT_MAX_DEPTH);
+        assert_eq!(config.timestamp, TimestampType::Date);
+        assert_eq!(config.duration, DurationType::Keyword);
+        assert_eq!(config.diagnostics, DiagnosticsFormat::Human);
+        assert_eq!(config.diagnostics_path, None);
+        assert!(config.include_paths.is_empty());
+        assert!(!config.werror);
+        assert!(config.allow.is_empty());
+        assert!(config.deny.is_empty());
+    }
+
+    #[test]
+    fn test_output() {
+        let config = Config::try_from("output=index").unwrap();
+        assert_eq!(config.output, Output::Index);
+    }
+
+    #[test]
+    fn test_output_templates() {
+        for (param, output) in [
+            ("output=index_template", Output::IndexTemplate),
+            ("output=component_template", Output::ComponentTemplate),
+            ("output=legacy_template", Output::LegacyTemplate),
+        ] {
+            assert_eq!(Config::try_from(param).unwrap().output, output);
+        }
+    }
+
+    #[test]
+    fn test_split_components() {
+        let config = Config::try_from("output=component_template,split_components").unwrap();
+        assert!(config.split_components);
+        assert!(matches!(
+            Config::try_from("split_components=true").unwrap_err(),
+            Error::InvalidRequest(_)
+        ));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let config = Config::try_from("max_depth=5").unwrap();
+        assert_eq!(config.max_depth, 5);
+    }
+
+    #[test]
+    fn test_invalid_max_depth() {
+        for param in ["max_depth=0", "max_depth=-1", "max_depth=deep"] {
+            assert!(matches!(
+                Config::try_from(param).unwrap_err(),
+                Error::InvalidRequest(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let config = Config::try_from("timestamp=date_nanos").unwrap();
+        assert_eq!(config.timestamp, TimestampType::DateNanos);
+        assert!(matches!(
+            Config::try_from("timestamp=long").unwrap_err(),
+            Error::InvalidRequest(_)
+        ));
+    }
+
+    #[test]
+    fn test_duration() {
+        let config = Config::try_from("duration=long").unwrap();
+        assert_eq!(config.duration, DurationType::Long);
+        assert!(matches!(
+            Config::try_from("duration=date").unwrap_err(),
+            Error::InvalidRequest(_)
+        ));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        for (param, format) in [
+            ("diagnostics=human", DiagnosticsFormat::Human),
+            ("diagnostics=rich", DiagnosticsFormat::Rich),
+            ("diagnostics=json", DiagnosticsFormat::Json),
+            ("diagnostics=sarif", DiagnosticsFormat::Sarif),
+        ] {
+            assert_eq!(Config::try_from(param).unwrap().diagnostics, format);
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_path() {
+        let config =
+            Config::try_from("diagnostics=sarif,diagnostics_path=out/protosearch.sarif").unwrap();
+        assert_eq!(
+            config.diagnostics_path.as_deref(),
+            Some(Path::new("out/protosearch.sarif"))
+        );
+    }
+
+    #[test]
+    fn test_include_paths() {
+        let config = Config::try_from("include_path=proto,include_path=vendor/proto").unwrap();
+        assert_eq!(
+            config.include_paths,
+            [Path::new("proto"), Path::new("vendor/proto")]
+        );
+    }
+
+    #[test]
+    fn test_lints() {
+        let config = Config::try_from("werror,allow=W001,allow=W004,deny=W002").unwrap();
+        assert!(config.werror);
+        assert_eq!(config.allow, ["W001", "W004"]);
+        assert_eq!(config.deny, ["W002"]);
+    }
+
+    #[test]
+    fn test_invalid_lints() {
+        for param in ["allow=E001", "deny=W1", "allow=w001", "werror=true"] {
+            assert!(matches!(
+                Config::try_from(param).unwrap_err(),
+                Error::InvalidRequest(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_diagnostics() {
+        for param in ["diagnostics=xml", "diagnostics_path="] {
+            assert!(matches!(
+                Config::try_from(param).unwrap_err(),
+                Error::InvalidRequest(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_output() {
+        assert!(matches!(
+            Config::try_from("output=unknown").unwrap_err(),
+            Error::InvalidRequest(_)
+        ));
     }
 
     #[test]
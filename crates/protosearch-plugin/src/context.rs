@@ -1,15 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use protobuf::plugin::CodeGeneratorRequest;
 use protobuf::reflect::FileDescriptor;
 
 use crate::Result;
-use crate::config::Config;
+use crate::config::{Config, Output};
+use crate::vendor::{VendorProperty, find_vendor_properties};
 
 pub struct Context {
     file_descriptors_by_name: HashMap<String, FileDescriptor>,
     pub files_to_generate: Vec<String>,
     config: Config,
+    vendor_properties: BTreeMap<u32, VendorProperty>,
 }
 
 impl TryFrom<CodeGeneratorRequest> for Context {
@@ -20,11 +22,13 @@ impl TryFrom<CodeGeneratorRequest> for Context {
         let file_descriptors_by_name = FileDescriptor::new_dynamic_fds(request.proto_file, &[])?
             .into_iter()
             .map(|fd| (fd.name().into(), fd))
-            .collect();
+            .collect::<HashMap<String, FileDescriptor>>();
+        let vendor_properties = find_vendor_properties(file_descriptors_by_name.values());
         Ok(Context {
             file_descriptors_by_name,
             files_to_generate: request.file_to_generate,
             config,
+            vendor_properties,
         })
     }
 }
@@ -34,6 +38,44 @@ impl Context {
         self.config.target.as_deref()
     }
 
+    pub fn output(&self) -> Output {
+        self.config.output
+    }
+
+    /// Whether to write the message fields of each document as component templates of their own.
+    pub fn split_components(&self) -> bool {
+        self.config.split_components
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.config.max_depth
+    }
+
+    /// The mapping type to infer for `google.protobuf.Timestamp` fields.
+    pub fn timestamp_type(&self) -> &'static str {
+        self.config.timestamp.as_str()
+    }
+
+    /// The mapping type to infer for `google.protobuf.Duration` fields.
+    pub fn duration_type(&self) -> &'static str {
+        self.config.duration.as_str()
+    }
+
+    /// Return whether the `allow` parameter suppresses the warning `code`.
+    pub fn is_allowed(&self, code: &str) -> bool {
+        self.config.allow.iter().any(|c| c == code)
+    }
+
+    /// Return whether the `werror` or `deny` parameters report the warning `code` as an error.
+    pub fn is_denied(&self, code: &str) -> bool {
+        self.config.werror || self.config.deny.iter().any(|c| c == code)
+    }
+
+    /// The typed vendor properties declared in the request, keyed by extension number.
+    pub fn vendor_properties(&self) -> &BTreeMap<u32, VendorProperty> {
+        &self.vendor_properties
+    }
+
     pub fn get_file_descriptor_by_name(&self, name: &str) -> Option<&FileDescriptor> {
         self.file_descriptors_by_name.get(name)
     }
@@ -0,0 +1,189 @@
+//! Elasticsearch date format expressions.
+//!
+//! <https://www.elastic.co/docs/reference/elasticsearch/mapping-reference/mapping-date-format>
+
+/// Built-in formats.
+///
+/// Most formats also have a `strict_` variant. See [`NON_STRICT_FORMATS`].
+const BUILTIN_FORMATS: &[&str] = &[
+    "basic_date",
+    "basic_date_time",
+    "basic_date_time_no_millis",
+    "basic_ordinal_date",
+    "basic_ordinal_date_time",
+    "basic_ordinal_date_time_no_millis",
+    "basic_t_time",
+    "basic_t_time_no_millis",
+    "basic_time",
+    "basic_time_no_millis",
+    "basic_week_date",
+    "basic_week_date_time",
+    "basic_week_date_time_no_millis",
+    "date",
+    "date_hour",
+    "date_hour_minute",
+    "date_hour_minute_second",
+    "date_hour_minute_second_fraction",
+    "date_hour_minute_second_millis",
+    "date_optional_time",
+    "date_optional_time_nanos",
+    "date_time",
+    "date_time_no_millis",
+    "epoch_millis",
+    "epoch_second",
+    "hour",
+    "hour_minute",
+    "hour_minute_second",
+    "hour_minute_second_fraction",
+    "hour_minute_second_millis",
+    "iso8601",
+    "ordinal_date",
+    "ordinal_date_time",
+    "ordinal_date_time_no_millis",
+    "t_time",
+    "t_time_no_millis",
+    "time",
+    "time_no_millis",
+    "week_date",
+    "week_date_time",
+    "week_date_time_no_millis",
+    "weekyear",
+    "weekyear_week",
+    "weekyear_week_day",
+    "year",
+    "year_month",
+    "year_month_day",
+];
+
+/// Built-in formats that have no `strict_` variant.
+const NON_STRICT_FORMATS: &[&str] = &[
+    "basic_date",
+    "basic_date_time",
+    "basic_date_time_no_millis",
+    "basic_ordinal_date",
+    "basic_ordinal_date_time",
+    "basic_ordinal_date_time_no_millis",
+    "basic_t_time",
+    "basic_t_time_no_millis",
+    "basic_time",
+    "basic_time_no_millis",
+    "epoch_millis",
+    "epoch_second",
+    "iso8601",
+];
+
+/// Java [`DateTimeFormatter`] pattern letters and the maximum number of times each may repeat.
+///
+/// [`DateTimeFormatter`]: https://docs.oracle.com/en/java/javase/21/docs/api/java.base/java/time/format/DateTimeFormatter.html
+const PATTERN_LETTERS: &[(char, usize)] = &[
+    ('A', 19),
+    ('D', 3),
+    ('E', 5),
+    ('F', 1),
+    ('G', 5),
+    ('H', 2),
+    ('K', 2),
+    ('L', 5),
+    ('M', 5),
+    ('N', 19),
+    ('O', 4),
+    ('Q', 5),
+    ('S', 9),
+    ('V', 2),
+    ('W', 1),
+    ('X', 5),
+    ('Y', 19),
+    ('Z', 5),
+    ('a', 1),
+    ('c', 5),
+    ('d', 2),
+    ('e', 5),
+    ('h', 2),
+    ('k', 2),
+    ('m', 2),
+    ('n', 19),
+    ('p', 19),
+    ('q', 5),
+    ('s', 2),
+    ('u', 19),
+    ('w', 2),
+    ('x', 5),
+    ('y', 19),
+    ('z', 4),
+];
+
+/// Check a date format expression, i.e., built-in format names and patterns separated by `||`.
+///
+/// Returns the reason the expression is invalid.
+pub fn validate(format: &str) -> Result<(), String> {
+    for alternative in format.split("||") {
+        if alternative.is_empty() {
+            return Err("contains an empty format".to_string());
+        }
+        if is_builtin(alternative) {
+            continue;
+        }
+        // Patterns cannot contain underscores outside of quotes, so this was meant to be a built-in.
+        if alternative.contains('_')
+            && alternative
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!("refers to unknown built-in format '{alternative}'"));
+        }
+        validate_pattern(alternative)
+            .map_err(|reason| format!("has invalid pattern '{alternative}': {reason}"))?;
+    }
+    Ok(())
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTIN_FORMATS.contains(&name)
+        || name.strip_prefix("strict_").is_some_and(|name| {
+            BUILTIN_FORMATS.contains(&name) && !NON_STRICT_FORMATS.contains(&name)
+        })
+}
+
+fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let mut chars = pattern.chars().peekable();
+    let mut optional_depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next() {
+                    // `''` is an escaped quote.
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                    }
+                    Some('\'') => break,
+                    Some(_) => {}
+                    None => return Err("unterminated quote".to_string()),
+                }
+            },
+            '[' => optional_depth += 1,
+            ']' if optional_depth == 0 => return Err("unmatched ']'".to_string()),
+            ']' => optional_depth -= 1,
+            '#' | '{' | '}' => return Err(format!("reserved character '{c}'")),
+            c if c.is_ascii_alphabetic() => {
+                let Some((_, max)) = PATTERN_LETTERS.iter().find(|(letter, _)| *letter == c) else {
+                    return Err(format!("unknown pattern letter '{c}'"));
+                };
+                let mut count = 1;
+                while chars.next_if_eq(&c).is_some() {
+                    count += 1;
+                }
+                if count > *max {
+                    return Err(format!(
+                        "too many pattern letters '{}'",
+                        c.to_string().repeat(count)
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    if optional_depth > 0 {
+        return Err("unclosed '['".to_string());
+    }
+    Ok(())
+}
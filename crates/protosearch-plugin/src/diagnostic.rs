@@ -1,15 +1,33 @@
 use std::fmt;
 
+use serde::Serialize;
+use serde::ser::{SerializeStruct, Serializer};
+
 use crate::span::Span;
+use crate::validator::FIELD_NAME_PATTERN;
+
+const DATE_FORMAT_URL: &str =
+    "https://www.elastic.co/docs/reference/elasticsearch/mapping-reference/mapping-date-format";
+const ALLOW_HELP: &str = "'allow' takes warning codes, e.g., 'W001'; errors cannot be allowed";
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Diagnostic {
     pub severity: Severity,
     pub kind: DiagnosticKind,
     pub location: Option<Location>,
+    /// Other locations involved in the problem, e.g., a conflicting field.
+    pub related: Vec<Related>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A secondary location attached to a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Related {
+    pub location: Location,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     Warning,
     Error,
@@ -39,17 +57,98 @@ pub enum DiagnosticKind {
         parameter: String,
         reason: String,
     },
+    InvalidDynamicTemplate {
+        message: String,
+        name: String,
+        reason: String,
+    },
+    RecursiveMessage {
+        message: String,
+        field: String,
+        type_name: String,
+    },
+    MaxDepthExceeded {
+        message: String,
+        field: String,
+        max_depth: usize,
+    },
     UnknownTarget {
         message: String,
         field: String,
         label: String,
     },
+    UnknownAnalyzer {
+        message: String,
+        field: String,
+        parameter: String,
+        name: String,
+    },
+    InapplicableParameter {
+        message: String,
+        field: String,
+        parameter: String,
+        type_name: String,
+    },
+    IncompatibleType {
+        message: String,
+        field: String,
+        proto_type: String,
+        type_name: String,
+        suggestion: String,
+    },
+    UnknownCopyToTarget {
+        message: String,
+        field: String,
+        path: String,
+    },
+    ShadowedProperty {
+        message: String,
+        name: String,
+    },
+    ConflictingOneofMember {
+        message: String,
+        oneof: String,
+        name: String,
+    },
+    DuplicateProperty {
+        message: String,
+        field: String,
+        name: String,
+    },
+    InvalidMultiField {
+        message: String,
+        field: String,
+        reason: String,
+    },
+    InvalidIndexOption {
+        message: String,
+        option: String,
+        reason: String,
+    },
+    ConflictingVendorProperties {
+        message: String,
+        field: String,
+        vendor: String,
+        types: Vec<String>,
+    },
+    UnknownSourcePath {
+        message: String,
+        option: String,
+        path: String,
+    },
+    RoutingNotRequired {
+        message: String,
+    },
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Location {
     pub file: String,
     pub span: Option<Span>,
+    /// The field names within the aggregate option value (`{...}`) at `span` that `protoc` does
+    /// not record a location for. See [`Sources::resolve`](crate::report::Sources::resolve).
+    #[serde(skip)]
+    pub option_path: Vec<String>,
 }
 
 impl Diagnostic {
@@ -58,6 +157,7 @@ impl Diagnostic {
             severity: Severity::Error,
             kind,
             location: None,
+            related: Vec::new(),
         }
     }
 
@@ -66,17 +166,32 @@ impl Diagnostic {
             severity: Severity::Warning,
             kind,
             location: None,
+            related: Vec::new(),
         }
     }
 
     pub fn at(self, location: Location) -> Self {
         Self {
-            severity: self.severity,
-            kind: self.kind,
             location: Some(location),
+            ..self
         }
     }
 
+    pub fn with_related(mut self, location: Location, message: impl Into<String>) -> Self {
+        self.related.push(Related {
+            location,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// The diagnostic code, e.g., `E001`.
+    ///
+    /// A warning reported as an error keeps its code.
+    pub fn code(&self) -> String {
+        format!("{}{:0>3}", self.kind.prefix(), self.kind.number())
+    }
+
     pub fn is_error(&self) -> bool {
         matches!(self.severity, Severity::Error)
     }
@@ -96,19 +211,150 @@ impl Severity {
 }
 
 impl DiagnosticKind {
+    /// The prefix of the code, i.e., `E` for errors and `W` for warnings.
+    pub fn prefix(&self) -> char {
+        match self {
+            Self::InvalidParameterValue { .. }
+            | Self::InvalidTargetJson { .. }
+            | Self::InvalidTargetJsonType { .. }
+            | Self::InvalidDynamicTemplate { .. }
+            | Self::RecursiveMessage { .. }
+            | Self::MaxDepthExceeded { .. }
+            | Self::DuplicateProperty { .. }
+            | Self::InvalidMultiField { .. }
+            | Self::InvalidIndexOption { .. }
+            | Self::ConflictingVendorProperties { .. } => Severity::Error.prefix(),
+            Self::InvalidFieldName { .. }
+            | Self::UnknownTarget { .. }
+            | Self::UnknownAnalyzer { .. }
+            | Self::ShadowedProperty { .. }
+            | Self::ConflictingOneofMember { .. }
+            | Self::InapplicableParameter { .. }
+            | Self::IncompatibleType { .. }
+            | Self::UnknownCopyToTarget { .. }
+            | Self::UnknownSourcePath { .. }
+            | Self::RoutingNotRequired { .. } => Severity::Warning.prefix(),
+        }
+    }
+
     pub fn number(&self) -> u32 {
         match self {
             // E
             Self::InvalidParameterValue { .. } => 1,
             Self::InvalidTargetJson { .. } => 2,
             Self::InvalidTargetJsonType { .. } => 3,
+            Self::InvalidDynamicTemplate { .. } => 4,
+            Self::RecursiveMessage { .. } => 5,
+            Self::MaxDepthExceeded { .. } => 6,
+            Self::DuplicateProperty { .. } => 7,
+            Self::InvalidMultiField { .. } => 8,
+            Self::InvalidIndexOption { .. } => 9,
+            Self::ConflictingVendorProperties { .. } => 10,
             // W
             Self::InvalidFieldName { .. } => 1,
             Self::UnknownTarget { .. } => 2,
+            Self::UnknownAnalyzer { .. } => 3,
+            Self::ShadowedProperty { .. } => 4,
+            Self::ConflictingOneofMember { .. } => 5,
+            Self::InapplicableParameter { .. } => 6,
+            Self::IncompatibleType { .. } => 7,
+            Self::UnknownCopyToTarget { .. } => 8,
+            Self::UnknownSourcePath { .. } => 9,
+            Self::RoutingNotRequired { .. } => 10,
         }
     }
 }
 
+impl DiagnosticKind {
+    /// A hint about how to fix the problem, if there is anything to add to the message.
+    pub fn help(&self) -> Option<String> {
+        let help = match self {
+            Self::InvalidTargetJson { .. } | Self::InvalidTargetJsonType { .. } => {
+                "a target's 'json' must be a JSON object of mapping parameters".to_string()
+            }
+            Self::InvalidFieldName { .. } => {
+                format!("field names should match '{FIELD_NAME_PATTERN}'")
+            }
+            Self::InvalidParameterValue { parameter, .. } => match parameter.as_str() {
+                "ignore_above" => "valid values are 1 to 2147483647".to_string(),
+                "position_increment_gap" => "valid values are 0 to 2147483647".to_string(),
+                "index_prefixes.min_chars" => {
+                    "valid values are 0 to one less than 'max_chars'".to_string()
+                }
+                "index_prefixes.max_chars" => "valid values are 0 to 20".to_string(),
+                "format" => format!("see {DATE_FORMAT_URL}"),
+                "null_value" => "'null_value' must have the same type as the field".to_string(),
+                "copy_to" => "copy to a leaf field in the same nested document or one of its parents"
+                    .to_string(),
+                "map_strategy" => {
+                    "'map_strategy' applies to map fields with string keys".to_string()
+                }
+                "allow" => ALLOW_HELP.to_string(),
+                _ => return None,
+            },
+            Self::InvalidDynamicTemplate { .. } => {
+                "a dynamic template needs a 'name' and either a 'mapping' or 'json'".to_string()
+            }
+            Self::MaxDepthExceeded { .. } => {
+                "raise the 'max_depth' plugin parameter, or set 'recursion' to stop here"
+                    .to_string()
+            }
+            Self::UnknownTarget { .. } => {
+                "the plugin uses the default mapping for fields without this target".to_string()
+            }
+            Self::UnknownAnalyzer { .. } => {
+                "declare it in '(protosearch.index).settings.analysis'".to_string()
+            }
+            Self::ShadowedProperty { .. } => {
+                "queries see the runtime field instead of the indexed property".to_string()
+            }
+            Self::ConflictingOneofMember { .. } => {
+                "the last member defines the property; give the members different names or the same type"
+                    .to_string()
+            }
+            Self::InapplicableParameter { .. } => {
+                "remove the parameter or change the field's 'type'".to_string()
+            }
+            Self::DuplicateProperty { .. } => {
+                "set 'name' on one of the fields to map it to a different property".to_string()
+            }
+            Self::UnknownCopyToTarget { .. } | Self::UnknownSourcePath { .. } => {
+                "paths use the property names, separated by dots, e.g., 'author.name'".to_string()
+            }
+            Self::RoutingNotRequired { .. } => "set '_routing.required' to true".to_string(),
+            Self::ConflictingVendorProperties { .. } => {
+                "a field maps to one property type for each vendor".to_string()
+            }
+            Self::RecursiveMessage { .. } => {
+                "'RECURSION_FLATTENED' maps it as 'flattened', 'RECURSION_DISABLED' as an 'object' with 'enabled: false'"
+                    .to_string()
+            }
+            Self::IncompatibleType { suggestion, .. } => {
+                format!("remove 'type' to map the field as '{suggestion}'")
+            }
+            Self::InvalidMultiField { .. } => {
+                "a multi-field needs a 'type' other than 'object' or 'nested', and a name without '.'"
+                    .to_string()
+            }
+            Self::InvalidIndexOption { option, .. } => match option.as_str() {
+                "_source.mode" => {
+                    "remove '_source.enabled'; 'SOURCE_MODE_DISABLED' disables '_source'".to_string()
+                }
+                "allow" => ALLOW_HELP.to_string(),
+                "dynamic_date_formats" => format!("see {DATE_FORMAT_URL}"),
+                "settings.routing_partition_size" => {
+                    "set '_routing.required' to true, or remove 'routing_partition_size'".to_string()
+                }
+                "_routing.required" => {
+                    "time series indices route documents by their dimensions".to_string()
+                }
+                _ => return None,
+            },
+        };
+        Some(help)
+    }
+}
+
 impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.span {
@@ -121,22 +367,28 @@ impl fmt::Display for Location {
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.location {
-            Some(loc) => write!(
-                f,
-                "{}: {}{:0>3} {}",
-                loc,
-                self.severity.prefix(),
-                self.kind.number(),
-                self.kind
-            ),
-            None => write!(
-                f,
-                "{}{:0>3} {}",
-                self.severity.prefix(),
-                self.kind.number(),
-                self.kind
-            ),
+            Some(loc) => write!(f, "{}: {} {}", loc, self.code(), self.kind),
+            None => write!(f, "{} {}", self.code(), self.kind),
+        }?;
+        for related in &self.related {
+            write!(f, "\n{}: note: {}", related.location, related.message)?;
         }
+        Ok(())
+    }
+}
+
+impl Serialize for Diagnostic {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Diagnostic", 5)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("severity", &self.severity)?;
+        state.serialize_field("message", &self.kind.to_string())?;
+        state.serialize_field("location", &self.location)?;
+        state.serialize_field("related", &self.related)?;
+        state.end()
     }
 }
 
@@ -173,11 +425,123 @@ impl fmt::Display for DiagnosticKind {
                 parameter,
                 reason,
             } => write!(f, "{message}.{field}: '{parameter}' {reason}"),
+            Self::InvalidDynamicTemplate {
+                message,
+                name,
+                reason,
+            } => write!(f, "{message}: dynamic template '{name}' {reason}"),
+            Self::RecursiveMessage {
+                message,
+                field,
+                type_name,
+            } => write!(
+                f,
+                "{message}.{field}: '{type_name}' contains itself; set 'recursion' to stop here"
+            ),
+            Self::MaxDepthExceeded {
+                message,
+                field,
+                max_depth,
+            } => write!(
+                f,
+                "{message}.{field}: mapping exceeds the maximum depth of {max_depth}"
+            ),
             Self::UnknownTarget {
                 message,
                 field,
                 label,
             } => write!(f, "{message}.{field}: unknown target '{label}'"),
+            Self::UnknownAnalyzer {
+                message,
+                field,
+                parameter,
+                name,
+            } => {
+                let component = match parameter.as_str() {
+                    "normalizer" => "normalizer",
+                    _ => "analyzer",
+                };
+                write!(
+                    f,
+                    "{message}.{field}: '{parameter}' refers to unknown {component} '{name}'"
+                )
+            }
+            Self::InapplicableParameter {
+                message,
+                field,
+                parameter,
+                type_name,
+            } => write!(
+                f,
+                "{message}.{field}: '{parameter}' does not apply to '{type_name}' fields"
+            ),
+            Self::IncompatibleType {
+                message,
+                field,
+                proto_type,
+                type_name,
+                suggestion,
+            } => write!(
+                f,
+                "{message}.{field}: '{proto_type}' field is mapped as '{type_name}'; did you mean '{suggestion}'?"
+            ),
+            Self::UnknownCopyToTarget {
+                message,
+                field,
+                path,
+            } => write!(
+                f,
+                "{message}.{field}: 'copy_to' target '{path}' does not exist"
+            ),
+            Self::ShadowedProperty { message, name } => write!(
+                f,
+                "{message}: runtime field '{name}' shadows a property with the same name"
+            ),
+            Self::DuplicateProperty {
+                message,
+                field,
+                name,
+            } => write!(f, "{message}.{field}: property '{name}' is already defined"),
+            Self::InvalidMultiField {
+                message,
+                field,
+                reason,
+            } => write!(f, "{message}.{field}: multi-field {reason}"),
+            Self::InvalidIndexOption {
+                message,
+                option,
+                reason,
+            } => write!(f, "{message}: '{option}' {reason}"),
+            Self::ConflictingVendorProperties {
+                message,
+                field,
+                vendor,
+                types,
+            } => write!(
+                f,
+                "{message}.{field}: only one '{vendor}' property may be set, but found '{}'",
+                types.join("', '")
+            ),
+            Self::UnknownSourcePath {
+                message,
+                option,
+                path,
+            } => write!(
+                f,
+                "{message}: '{option}' path '{path}' does not match any property"
+            ),
+            Self::RoutingNotRequired { message } => write!(
+                f,
+                "{message}: '_routing.required' is false, but join fields require routing"
+            ),
+            Self::ConflictingOneofMember {
+                message,
+                oneof,
+                name,
+            } => write!(
+                f,
+                "{message}.{oneof}: members map to '{name}' with conflicting types"
+            ),
         }
     }
 }
@@ -1,19 +1,24 @@
 mod config;
 mod context;
+mod date_format;
 mod diagnostic;
 mod error;
 mod mapping;
 mod options;
+mod parameters;
 mod plugin;
+pub mod report;
 mod span;
 mod validator;
+mod vendor;
 
 #[allow(warnings, clippy::all)]
 mod proto {
     include!(concat!(env!("OUT_DIR"), "/protosearch.rs"));
 }
 
-pub use diagnostic::{Diagnostic, DiagnosticKind, Location};
+pub use config::{Config, DiagnosticsFormat};
+pub use diagnostic::{Diagnostic, DiagnosticKind, Location, Related, Severity};
 pub use error::{Error, Result};
 pub use plugin::process;
 pub use span::{Point, Span};
@@ -31,6 +36,7 @@ mod tests {
     use serde_json::Value;
 
     use crate::diagnostic::DiagnosticKind;
+    use crate::span::{Point, Span};
 
     static DESCRIPTORS: LazyLock<Vec<FileDescriptorProto>> = LazyLock::new(|| {
         let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -53,6 +59,14 @@ mod tests {
     });
 
     macro_rules! test_snapshot {
+        ($name:ident, $test:expr, parameter = $parameter:expr) => {
+            #[test]
+            fn $name() {
+                let req = make_request_with_parameter("tests/tests.proto", $parameter);
+                let (resp, _diagnostics) = crate::process(req).unwrap();
+                insta::assert_json_snapshot!(output_for(&resp, $test));
+            }
+        };
         ($name:ident, $test:expr, $target:expr) => {
             #[test]
             fn $name() {
@@ -64,9 +78,17 @@ mod tests {
     }
 
     fn make_request(file_to_generate: &str, target: Option<&str>) -> CodeGeneratorRequest {
+        let parameter = target.map(|t| format!("target={t}")).unwrap_or_default();
+        make_request_with_parameter(file_to_generate, &parameter)
+    }
+
+    fn make_request_with_parameter(
+        file_to_generate: &str,
+        parameter: &str,
+    ) -> CodeGeneratorRequest {
         let mut req = CodeGeneratorRequest::new();
-        if let Some(t) = target {
-            req.set_parameter(format!("target={t}"))
+        if !parameter.is_empty() {
+            req.set_parameter(parameter.to_string())
         }
         req.file_to_generate.push(file_to_generate.to_string());
         req.proto_file = DESCRIPTORS.clone();
@@ -90,12 +112,92 @@ mod tests {
     test_snapshot!(test_no_target, "tests.FieldTestCase", None);
     test_snapshot!(test_infer_type, "tests.InferTypeTestCase", None);
     test_snapshot!(test_target, "tests.FieldTestCase", Some("foo"));
+    test_snapshot!(test_any, "tests.AnyTestCase", None);
+    test_snapshot!(
+        test_timestamp_date_nanos,
+        "tests.TimestampTestCase",
+        parameter = "timestamp=date_nanos"
+    );
+    test_snapshot!(
+        test_duration_long,
+        "tests.DurationTestCase",
+        parameter = "duration=long"
+    );
     test_snapshot!(test_index_params, "tests.IndexTestCase", None);
     test_snapshot!(test_enum, "tests.EnumTestCase", None);
     test_snapshot!(test_nested, "tests.MessageTestCase", None);
     test_snapshot!(test_dynamic, "tests.DynamicTestCase", None);
     test_snapshot!(test_index_options, "tests.IndexOptionsTestCase", None);
     test_snapshot!(test_term_vector, "tests.TermVectorTestCase", None);
+    test_snapshot!(test_index_settings, "tests.IndexSettingsTestCase", None);
+    test_snapshot!(
+        test_output_index,
+        "tests.IndexSettingsTestCase",
+        parameter = "output=index"
+    );
+    test_snapshot!(test_index_template, "tests.IndexTemplateTestCase", None);
+    test_snapshot!(
+        test_dynamic_templates,
+        "tests.DynamicTemplatesTestCase",
+        None
+    );
+    test_snapshot!(test_runtime, "tests.RuntimeTestCase", None);
+    test_snapshot!(test_map, "tests.MapTestCase", None);
+    test_snapshot!(test_oneof, "tests.OneofTestCase", None);
+    test_snapshot!(test_recursion, "tests.RecursionTestCase", None);
+    test_snapshot!(test_vendor, "tests.VendorTestCase", Some("vendor"));
+    test_snapshot!(test_vendor_no_target, "tests.VendorTestCase", None);
+    test_snapshot!(
+        test_max_depth,
+        "tests.MaxDepthTestCase",
+        parameter = "max_depth=3"
+    );
+    test_snapshot!(
+        test_output_index_template,
+        "tests.IndexTemplateTestCase",
+        parameter = "output=index_template"
+    );
+    test_snapshot!(
+        test_output_component_template,
+        "tests.IndexTemplateTestCase",
+        parameter = "output=component_template"
+    );
+    test_snapshot!(
+        test_output_legacy_template,
+        "tests.IndexTemplateTestCase",
+        parameter = "output=legacy_template"
+    );
+
+    macro_rules! test_split_components {
+        ($name:ident, $output:literal) => {
+            #[test]
+            fn $name() {
+                let req = make_request_with_parameter(
+                    "tests/tests.proto",
+                    concat!("output=", $output, ",split_components"),
+                );
+                let (resp, _diagnostics) = crate::process(req).unwrap();
+                let outputs: BTreeMap<String, Value> = resp
+                    .file
+                    .iter()
+                    .filter(|f| f.name().starts_with("tests.SplitComponents"))
+                    .map(|f| {
+                        (
+                            f.name().to_string(),
+                            serde_json::from_str(f.content()).unwrap(),
+                        )
+                    })
+                    .collect();
+                insta::assert_json_snapshot!(outputs);
+            }
+        };
+    }
+
+    test_split_components!(
+        test_split_components_component_template,
+        "component_template"
+    );
+    test_split_components!(test_split_components_index_template, "index_template");
 
     macro_rules! test_field_name {
         ($test_name:ident, $name:literal, true) => {
@@ -211,6 +313,695 @@ mod tests {
         false
     );
 
+    test_parameter_value!(
+        test_map_strategy_string_keys,
+        "map_strategy_string_keys",
+        "map_strategy",
+        true
+    );
+    test_parameter_value!(
+        test_map_strategy_key_value,
+        "map_strategy_key_value",
+        "map_strategy",
+        true
+    );
+    test_parameter_value!(
+        test_map_strategy_int_keys,
+        "map_strategy_int_keys",
+        "map_strategy",
+        false
+    );
+    test_parameter_value!(
+        test_map_strategy_bool_keys,
+        "map_strategy_bool_keys",
+        "map_strategy",
+        false
+    );
+    test_parameter_value!(
+        test_map_strategy_not_map,
+        "map_strategy_not_map",
+        "map_strategy",
+        false
+    );
+
+    macro_rules! test_analyzer {
+        ($test_name:ident, $field:literal, $parameter:literal, true) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert!(!diagnostics.iter().any(|d| matches!(
+                    &d.kind,
+                    DiagnosticKind::UnknownAnalyzer { field, parameter, .. } if field == $field && parameter == $parameter
+                )));
+            }
+        };
+        ($test_name:ident, $field:literal, $parameter:literal, false) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert!(diagnostics.iter().any(|d| matches!(
+                    &d.kind,
+                    DiagnosticKind::UnknownAnalyzer { field, parameter, .. } if field == $field && parameter == $parameter
+                )));
+            }
+        };
+    }
+
+    test_analyzer!(
+        test_analyzer_declared,
+        "declared_analyzer",
+        "analyzer",
+        true
+    );
+    test_analyzer!(
+        test_search_analyzer_declared,
+        "declared_analyzer",
+        "search_analyzer",
+        true
+    );
+    test_analyzer!(test_analyzer_builtin, "builtin_analyzer", "analyzer", true);
+    test_analyzer!(
+        test_normalizer_declared,
+        "declared_normalizer",
+        "normalizer",
+        true
+    );
+    test_analyzer!(
+        test_normalizer_builtin,
+        "builtin_normalizer",
+        "normalizer",
+        true
+    );
+    test_analyzer!(test_analyzer_unknown, "unknown_analyzer", "analyzer", false);
+    test_analyzer!(
+        test_search_analyzer_unknown,
+        "unknown_search_analyzer",
+        "search_analyzer",
+        false
+    );
+    test_analyzer!(
+        test_normalizer_unknown,
+        "unknown_normalizer",
+        "normalizer",
+        false
+    );
+
+    macro_rules! test_dynamic_template {
+        ($test_name:ident, $name:literal, $reason:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                let expected = DiagnosticKind::InvalidDynamicTemplate {
+                    message: "tests.InvalidDynamicTemplateTestCase".to_string(),
+                    name: $name.to_string(),
+                    reason: $reason.to_string(),
+                };
+                assert!(diagnostics.iter().any(|d| d.kind == expected));
+            }
+        };
+    }
+
+    test_dynamic_template!(test_dynamic_template_no_name, "", "must have a name");
+    test_dynamic_template!(
+        test_dynamic_template_no_mapping,
+        "no_mapping",
+        "must have a mapping"
+    );
+    test_dynamic_template!(
+        test_dynamic_template_invalid_json,
+        "invalid_json",
+        "'json' is not valid JSON"
+    );
+    test_dynamic_template!(
+        test_dynamic_template_non_object_json,
+        "non_object_json",
+        "'json' must be a JSON object"
+    );
+
+    macro_rules! test_shadowed_property {
+        ($test_name:ident, $name:literal, $shadowed:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                let expected = DiagnosticKind::ShadowedProperty {
+                    message: "tests.RuntimeTestCase".to_string(),
+                    name: $name.to_string(),
+                };
+                assert_eq!(diagnostics.iter().any(|d| d.kind == expected), $shadowed);
+            }
+        };
+    }
+
+    test_shadowed_property!(test_runtime_field_unique, "day_of_week", false);
+    test_shadowed_property!(test_runtime_field_shadows_property, "title", true);
+    test_shadowed_property!(
+        test_runtime_field_shadows_nested_property,
+        "author.name",
+        true
+    );
+    test_shadowed_property!(test_runtime_field_nested_unique, "author.email", false);
+
+    macro_rules! test_conflicting_oneof_member {
+        ($test_name:ident, $message:literal, $name:literal, $conflicting:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                let expected = DiagnosticKind::ConflictingOneofMember {
+                    message: $message.to_string(),
+                    oneof: "value".to_string(),
+                    name: $name.to_string(),
+                };
+                assert_eq!(diagnostics.iter().any(|d| d.kind == expected), $conflicting);
+            }
+        };
+    }
+
+    test_conflicting_oneof_member!(
+        test_oneof_members_conflicting_types,
+        "ConflictingOneofMemberTestCase",
+        "value",
+        true
+    );
+    test_conflicting_oneof_member!(
+        test_oneof_members_same_type,
+        "ConflictingOneofMemberTestCase",
+        "same",
+        false
+    );
+    test_conflicting_oneof_member!(
+        test_unannotated_oneof_members_conflicting_types,
+        "UnannotatedOneofTestCase",
+        "value",
+        true
+    );
+
+    macro_rules! test_duplicate_property {
+        ($test_name:ident, $field:literal, $duplicate:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert_eq!(
+                    diagnostics.iter().any(|d| matches!(
+                        &d.kind,
+                        DiagnosticKind::DuplicateProperty { message, field, .. }
+                            if message == "tests.DuplicatePropertyTestCase" && field == $field
+                    )),
+                    $duplicate
+                );
+            }
+        };
+    }
+
+    test_duplicate_property!(test_duplicate_property_renamed, "headline", true);
+    test_duplicate_property!(test_duplicate_property_dotted, "author_name", true);
+    test_duplicate_property!(test_duplicate_property_unique, "summary", false);
+    test_duplicate_property!(test_duplicate_property_oneof, "value_keyword", false);
+
+    #[test]
+    fn test_duplicate_property_oneof_member() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let duplicates: Vec<_> = diagnostics
+            .iter()
+            .filter_map(|d| match &d.kind {
+                DiagnosticKind::DuplicateProperty { message, field, .. }
+                    if message == "tests.OneofDuplicatePropertyTestCase" =>
+                {
+                    Some(field.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(duplicates, ["value_text", "value_int"]);
+    }
+
+    #[test]
+    fn test_duplicate_property_oneof_object() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let duplicates: Vec<_> = diagnostics
+            .iter()
+            .filter_map(|d| match &d.kind {
+                DiagnosticKind::DuplicateProperty {
+                    message,
+                    field,
+                    name,
+                } if message == "tests.OneofObjectDuplicatePropertyTestCase" => {
+                    Some((field.as_str(), name.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(duplicates, [("user_id", "target")]);
+    }
+
+    macro_rules! test_inapplicable_parameter {
+        ($test_name:ident, $field:literal, $parameter:literal, $inapplicable:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert_eq!(
+                    diagnostics.iter().any(|d| matches!(
+                        &d.kind,
+                        DiagnosticKind::InapplicableParameter { message, field, parameter, .. }
+                            if message == "tests.InapplicableParameterTestCase"
+                                && field == $field
+                                && parameter == $parameter
+                    )),
+                    $inapplicable
+                );
+            }
+        };
+    }
+
+    test_inapplicable_parameter!(
+        test_inapplicable_parameter_analyzer,
+        "analyzed_long",
+        "analyzer",
+        true
+    );
+    test_inapplicable_parameter!(
+        test_inapplicable_parameter_ignore_above,
+        "ignore_above_text",
+        "ignore_above",
+        true
+    );
+    test_inapplicable_parameter!(
+        test_applicable_parameter_ignore_above,
+        "ignore_above_keyword",
+        "ignore_above",
+        false
+    );
+    test_inapplicable_parameter!(
+        test_inapplicable_parameter_inferred_type,
+        "inferred_format",
+        "format",
+        true
+    );
+    test_inapplicable_parameter!(
+        test_applicable_parameter_inferred_type,
+        "inferred_date",
+        "format",
+        false
+    );
+    test_inapplicable_parameter!(
+        test_inapplicable_parameter_unknown_type,
+        "unknown_type",
+        "analyzer",
+        false
+    );
+
+    macro_rules! test_incompatible_type {
+        ($test_name:ident, $field:literal, $incompatible:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert_eq!(
+                    diagnostics.iter().any(|d| matches!(
+                        &d.kind,
+                        DiagnosticKind::IncompatibleType { message, field, .. }
+                            if message == "tests.IncompatibleTypeTestCase" && field == $field
+                    )),
+                    $incompatible
+                );
+            }
+        };
+    }
+
+    test_incompatible_type!(test_incompatible_type_string_long, "string_long", true);
+    test_incompatible_type!(test_incompatible_type_bool_date, "bool_date", true);
+    test_incompatible_type!(test_incompatible_type_bytes_text, "bytes_text", true);
+    test_incompatible_type!(test_incompatible_type_wrapper, "wrapper_boolean", true);
+    test_incompatible_type!(test_compatible_type_string_date, "string_date", false);
+    test_incompatible_type!(test_compatible_type_string_ip, "string_ip", false);
+    test_incompatible_type!(test_compatible_type_int_date, "int_date", false);
+    test_incompatible_type!(test_compatible_type_enum_integer, "enum_integer", false);
+    test_incompatible_type!(
+        test_compatible_type_timestamp,
+        "timestamp_date_nanos",
+        false
+    );
+    test_incompatible_type!(test_compatible_type_non_scalar, "string_geo_point", false);
+    test_incompatible_type!(test_compatible_type_duration_long, "duration_long", false);
+
+    #[test]
+    fn test_incompatible_type_suggestion() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| {
+                matches!(&d.kind, DiagnosticKind::IncompatibleType { field, .. } if field == "string_long")
+            })
+            .unwrap();
+        assert_eq!(
+            diagnostic.to_string(),
+            format!(
+                "tests/tests.proto:{}:27: W007 tests.IncompatibleTypeTestCase.string_long: 'string' field is mapped as 'long'; did you mean 'keyword'?",
+                line_of("string string_long = 1")
+            )
+        );
+    }
+
+    macro_rules! test_copy_to {
+        ($test_name:ident, $field:literal, $valid:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert_eq!(
+                    !diagnostics.iter().any(|d| matches!(
+                        &d.kind,
+                        DiagnosticKind::InvalidParameterValue { message, field, parameter, .. }
+                            if message.starts_with("tests.CopyToTestCase")
+                                && field == $field
+                                && parameter == "copy_to"
+                    ) || matches!(
+                        &d.kind,
+                        DiagnosticKind::UnknownCopyToTarget { field, .. } if field == $field
+                    )),
+                    $valid
+                );
+            }
+        };
+    }
+
+    test_copy_to!(test_copy_to_renamed, "title", true);
+    test_copy_to!(test_copy_to_dotted, "nested_target", true);
+    test_copy_to!(test_copy_to_from_object, "name", true);
+    test_copy_to!(test_copy_to_from_nested_to_parent, "body", true);
+    test_copy_to!(test_copy_to_within_nested, "commenter", true);
+    test_copy_to!(test_copy_to_unknown_target, "unknown_target", false);
+    test_copy_to!(test_copy_to_object, "object_target", false);
+    test_copy_to!(test_copy_to_nested_boundary, "nested_boundary", false);
+
+    #[test]
+    fn test_copy_to_reasons() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let messages: Vec<String> = diagnostics
+            .iter()
+            .filter(|d| {
+                matches!(
+                    &d.kind,
+                    DiagnosticKind::InvalidParameterValue { message, .. }
+                    | DiagnosticKind::UnknownCopyToTarget { message, .. }
+                        if message == "tests.CopyToTestCase"
+                )
+            })
+            .map(|d| d.kind.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "tests.CopyToTestCase.nested_boundary: 'copy_to' cannot copy into nested field 'comments'",
+                "tests.CopyToTestCase.object_target: 'copy_to' cannot copy to object field 'author'",
+                "tests.CopyToTestCase.unknown_target: 'copy_to' target 'missing' does not exist",
+            ]
+        );
+    }
+
+    macro_rules! test_null_value {
+        ($test_name:ident, $field:literal, $valid:literal) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                assert_eq!(
+                    !diagnostics.iter().any(|d| matches!(
+                        &d.kind,
+                        DiagnosticKind::InvalidParameterValue { message, field, parameter, .. }
+                            if message == "tests.NullValueTestCase"
+                                && field == $field
+                                && parameter == "null_value"
+                    )),
+                    $valid
+                );
+            }
+        };
+    }
+
+    test_null_value!(test_null_value_integer_number, "integer_number", true);
+    test_null_value!(test_null_value_integer_string, "integer_string", false);
+    test_null_value!(test_null_value_keyword_string, "keyword_string", true);
+    test_null_value!(test_null_value_keyword_bool, "keyword_bool", false);
+    test_null_value!(test_null_value_boolean_bool, "boolean_bool", true);
+    test_null_value!(test_null_value_boolean_number, "boolean_number", false);
+    test_null_value!(test_null_value_date_string, "date_string", true);
+    test_null_value!(test_null_value_date_number, "date_number", true);
+    test_null_value!(test_null_value_ip_number, "ip_number", false);
+    test_null_value!(test_null_value_text, "text_string", false);
+    test_null_value!(test_null_value_text_null, "text_null", true);
+
+    #[test]
+    fn test_integral_number_value() {
+        let req = make_request("tests/tests.proto", None);
+        let (resp, _diagnostics) = crate::process(req).unwrap();
+        let output = output_for(&resp, "tests.NumberValueTestCase");
+        let properties = &output["tests.NumberValueTestCase.json"]["properties"];
+        assert_eq!(properties["integral"]["null_value"], serde_json::json!(3));
+        assert_eq!(
+            properties["fractional"]["null_value"],
+            serde_json::json!(0.5)
+        );
+        // Typed `double` fields keep their fraction.
+        assert_eq!(properties["boosted"]["boost"], serde_json::json!(2.0));
+    }
+
+    macro_rules! test_date_format {
+        ($test_name:ident, $field:literal, $reason:expr) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                let reason: Option<&str> = $reason;
+                let actual = diagnostics.iter().find_map(|d| match &d.kind {
+                    DiagnosticKind::InvalidParameterValue {
+                        message,
+                        field,
+                        parameter,
+                        reason,
+                    } if message == "tests.DateFormatTestCase"
+                        && field == $field
+                        && parameter == "format" =>
+                    {
+                        Some(reason.as_str())
+                    }
+                    _ => None,
+                });
+                assert_eq!(actual, reason);
+            }
+        };
+    }
+
+    test_date_format!(test_date_format_builtin, "builtin", None);
+    test_date_format!(test_date_format_strict_builtin, "strict_builtin", None);
+    test_date_format!(test_date_format_alternatives, "alternatives", None);
+    test_date_format!(test_date_format_quoted, "quoted", None);
+    test_date_format!(test_date_format_optional, "optional", None);
+    test_date_format!(
+        test_date_format_unknown_builtin,
+        "unknown_builtin",
+        Some("refers to unknown built-in format 'strict_date_optinal_time'")
+    );
+    test_date_format!(
+        test_date_format_strict_epoch,
+        "strict_epoch",
+        Some("refers to unknown built-in format 'strict_epoch_millis'")
+    );
+    test_date_format!(
+        test_date_format_strict_iso8601,
+        "strict_iso8601",
+        Some("refers to unknown built-in format 'strict_iso8601'")
+    );
+    test_date_format!(
+        test_date_format_strict_basic,
+        "strict_basic_date",
+        Some("refers to unknown built-in format 'strict_basic_date'")
+    );
+    test_date_format!(
+        test_date_format_strict_basic_week,
+        "strict_basic_week_date",
+        None
+    );
+    test_date_format!(
+        test_date_format_unknown_letter,
+        "unknown_letter",
+        Some("has invalid pattern 'yyyy-MM-dd tt': unknown pattern letter 't'")
+    );
+    test_date_format!(
+        test_date_format_too_many_letters,
+        "too_many_letters",
+        Some("has invalid pattern 'yyyy-MMMMMM-dd': too many pattern letters 'MMMMMM'")
+    );
+    test_date_format!(
+        test_date_format_unterminated_quote,
+        "unterminated_quote",
+        Some("has invalid pattern 'yyyy-MM-dd'T': unterminated quote")
+    );
+    test_date_format!(
+        test_date_format_unclosed_optional,
+        "unclosed_optional",
+        Some("has invalid pattern 'yyyy[-MM': unclosed '['")
+    );
+    test_date_format!(
+        test_date_format_empty_alternative,
+        "empty_alternative",
+        Some("contains an empty format")
+    );
+
+    #[test]
+    fn test_date_format_non_date() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        assert!(diagnostics.iter().any(|d| matches!(
+            &d.kind,
+            DiagnosticKind::InapplicableParameter { message, field, parameter, .. }
+                if message == "tests.DateFormatTestCase" && field == "keyword" && parameter == "format"
+        )));
+    }
+
+    macro_rules! test_multi_field {
+        ($test_name:ident, $field:literal, $reason:expr) => {
+            #[test]
+            fn $test_name() {
+                let req = make_request("tests/tests.proto", None);
+                let (_resp, diagnostics) = crate::process(req).unwrap();
+                let reason: Option<&str> = $reason;
+                let actual = diagnostics.iter().find_map(|d| match &d.kind {
+                    DiagnosticKind::InvalidMultiField {
+                        message,
+                        field,
+                        reason,
+                    } if message == "tests.MultiFieldTestCase" && field == $field => {
+                        Some(reason.as_str())
+                    }
+                    _ => None,
+                });
+                assert_eq!(actual, reason);
+            }
+        };
+    }
+
+    test_multi_field!(test_multi_field_valid, "title.raw", None);
+    test_multi_field!(
+        test_multi_field_dotted_name,
+        "title.dotted.name",
+        Some("name cannot contain '.'")
+    );
+    test_multi_field!(
+        test_multi_field_untyped,
+        "title.untyped",
+        Some("must have a 'type'")
+    );
+    test_multi_field!(
+        test_multi_field_object,
+        "title.object",
+        Some("cannot be 'object'")
+    );
+    test_multi_field!(
+        test_multi_field_nested_fields,
+        "title.nested_fields",
+        Some("cannot have 'fields'")
+    );
+    test_multi_field!(
+        test_multi_field_copy_to,
+        "title.copy_to",
+        Some("cannot have 'copy_to'")
+    );
+
+    #[test]
+    fn test_multi_field_checks() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let ignore_above = diagnostics
+            .iter()
+            .find(|d| {
+                matches!(
+                    &d.kind,
+                    DiagnosticKind::InvalidParameterValue { message, field, parameter, .. }
+                        if message == "tests.MultiFieldTestCase"
+                            && field == "title.zero_ignore_above"
+                            && parameter == "ignore_above"
+                )
+            })
+            .unwrap();
+        // Diagnostics for a multi-field point at the field that declares it.
+        assert!(ignore_above.location.as_ref().unwrap().span.is_some());
+        assert!(diagnostics.iter().any(|d| matches!(
+            &d.kind,
+            DiagnosticKind::UnknownAnalyzer { message, field, .. }
+                if message == "tests.MultiFieldTestCase" && field == "title.unknown_analyzer"
+        )));
+        assert!(!diagnostics.iter().any(|d| matches!(
+            &d.kind,
+            DiagnosticKind::UnknownAnalyzer { field, .. } if field == "title.english"
+        )));
+    }
+
+    #[test]
+    fn test_duplicate_property_related() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| {
+                matches!(&d.kind, DiagnosticKind::DuplicateProperty { field, .. } if field == "author_name")
+            })
+            .unwrap();
+        assert!(diagnostic.location.as_ref().unwrap().span.is_some());
+        assert_eq!(diagnostic.related.len(), 1);
+        assert_eq!(
+            diagnostic.related[0].message,
+            "'tests.DuplicatePropertyTestCase.Author.name' also maps to this property"
+        );
+        assert!(diagnostic.related[0].location.span.is_some());
+    }
+
+    #[test]
+    fn test_recursive_message() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let expected = DiagnosticKind::RecursiveMessage {
+            message: "RecursiveMessageTestCase".to_string(),
+            field: "children".to_string(),
+            type_name: "tests.RecursiveMessageTestCase".to_string(),
+        };
+        assert!(diagnostics.iter().any(|d| d.kind == expected));
+    }
+
+    #[test]
+    fn test_mutually_recursive_message() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let expected = DiagnosticKind::RecursiveMessage {
+            message: "Child".to_string(),
+            field: "parent".to_string(),
+            type_name: "tests.MutuallyRecursiveMessageTestCase".to_string(),
+        };
+        assert!(diagnostics.iter().any(|d| d.kind == expected));
+    }
+
+    #[test]
+    fn test_max_depth_exceeded() {
+        let req = make_request_with_parameter("tests/tests.proto", "max_depth=2");
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        let expected = DiagnosticKind::MaxDepthExceeded {
+            message: "Level1".to_string(),
+            field: "level2".to_string(),
+            max_depth: 2,
+        };
+        assert!(diagnostics.iter().any(|d| d.kind == expected));
+    }
+
     #[test]
     fn test_invalid_json_target_string() {
         let req = make_request("tests/tests.proto", Some("invalid-json-string"));
@@ -247,6 +1038,114 @@ mod tests {
         assert!(diagnostics.iter().any(|d| d.kind == expected));
     }
 
+    /// Find the line number of the first line in `tests.proto` that contains `needle`.
+    fn line_of(needle: &str) -> u32 {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../proto/tests/tests.proto");
+        let source = std::fs::read_to_string(path).unwrap();
+        source.lines().position(|l| l.contains(needle)).unwrap() as u32 + 1
+    }
+
+    fn index_diagnostics(message: &str) -> Vec<(String, Option<u32>)> {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        diagnostics
+            .iter()
+            .filter(|d| d.kind.to_string().starts_with(&format!("{message}: ")))
+            .map(|d| {
+                let line = d
+                    .location
+                    .as_ref()
+                    .and_then(|l| l.span)
+                    .map(|s| s.start.line);
+                (d.kind.to_string(), line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_source_options() {
+        assert_eq!(
+            index_diagnostics("tests.SourceTestCase"),
+            [
+                (
+                    "tests.SourceTestCase: '_source.mode' cannot be set with '_source.enabled'"
+                        .to_string(),
+                    Some(line_of("_source.mode = SOURCE_MODE_STORED")),
+                ),
+                (
+                    "tests.SourceTestCase: '_source.includes' path 'missing' does not match any property"
+                        .to_string(),
+                    Some(line_of("_source.includes = \"missing\"")),
+                ),
+                (
+                    "tests.SourceTestCase: '_source.excludes' path 'missing.*' does not match any property"
+                        .to_string(),
+                    Some(line_of("_source.excludes = \"missing.*\"")),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_date_formats() {
+        assert_eq!(
+            index_diagnostics("tests.DynamicDateFormatsTestCase"),
+            [(
+                "tests.DynamicDateFormatsTestCase: 'dynamic_date_formats' has invalid pattern 'yyyy/MM/dd tt': unknown pattern letter 't'"
+                    .to_string(),
+                Some(line_of("dynamic_date_formats = \"yyyy/MM/dd tt\"")),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_routing_not_required() {
+        assert_eq!(
+            index_diagnostics("tests.RoutingTestCase"),
+            [(
+                "tests.RoutingTestCase: '_routing.required' is false, but join fields require routing"
+                    .to_string(),
+                Some(line_of("_routing.required = false")),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_routing_partition() {
+        assert_eq!(
+            index_diagnostics("tests.RoutingPartitionTestCase"),
+            [(
+                "tests.RoutingPartitionTestCase: 'settings.routing_partition_size' requires '_routing.required: true'"
+                    .to_string(),
+                Some(line_of("settings.routing_partition_size = 2")),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_routing_time_series() {
+        assert_eq!(
+            index_diagnostics("tests.RoutingTimeSeriesTestCase"),
+            [(
+                "tests.RoutingTimeSeriesTestCase: '_routing.required' cannot be true in a time series index"
+                    .to_string(),
+                Some(line_of("_routing.required = true")),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_index_option_location() {
+        assert_eq!(
+            index_diagnostics("tests.IndexOptionLocationTestCase"),
+            [(
+                "tests.IndexOptionLocationTestCase: '_source.excludes' path 'missing' does not match any property"
+                    .to_string(),
+                Some(line_of("message IndexOptionLocationTestCase") + 1),
+            )]
+        );
+    }
+
     #[test]
     fn test_nested_field_location() {
         let req = make_request("tests/tests.proto", None);
@@ -273,6 +1172,375 @@ mod tests {
         );
     }
 
+    /// Find the start line of the first diagnostic for `field` in `message` that matches `kind`.
+    fn field_diagnostic_line(message: &str, field: &str, kind: &str) -> Option<u32> {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        diagnostics
+            .iter()
+            .find(|d| {
+                d.kind
+                    .to_string()
+                    .starts_with(&format!("{message}.{field}: {kind}"))
+            })
+            .and_then(|d| d.location.as_ref())
+            .and_then(|l| l.span)
+            .map(|s| s.start.line)
+    }
+
+    #[test]
+    fn test_field_option_location() {
+        assert_eq!(
+            field_diagnostic_line(
+                "tests.OptionLocationTestCase",
+                "individual",
+                "'ignore_above'"
+            ),
+            Some(line_of("(protosearch.field).mapping.ignore_above = 0")),
+        );
+    }
+
+    #[test]
+    fn test_field_option_location_aggregate() {
+        assert_eq!(
+            field_diagnostic_line(
+                "tests.OptionLocationTestCase",
+                "aggregate",
+                "'ignore_above'"
+            ),
+            Some(line_of("string aggregate = 2")),
+        );
+    }
+
+    /// Find the span of the first diagnostic for `field` in `message` that matches `kind`, after
+    /// resolving it against the source.
+    fn resolved_field_diagnostic_span(message: &str, field: &str, kind: &str) -> Option<Span> {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, mut diagnostics) = crate::process(req).unwrap();
+        let include_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../proto");
+        crate::report::Sources::load(&[include_path], &diagnostics).resolve(&mut diagnostics);
+        diagnostics
+            .iter()
+            .find(|d| {
+                d.kind
+                    .to_string()
+                    .starts_with(&format!("{message}.{field}: {kind}"))
+            })
+            .and_then(|d| d.location.as_ref())
+            .and_then(|l| l.span)
+    }
+
+    /// The span of `needle` on line number `line` of `tests.proto`.
+    fn span_of(line: u32, needle: &str) -> Span {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../proto/tests/tests.proto");
+        let source = std::fs::read_to_string(path).unwrap();
+        let text = source.lines().nth(line as usize - 1).unwrap();
+        let column = text.find(needle).unwrap() as u32 + 1;
+        Span::new(
+            Point::new(line, column),
+            Point::new(line, column + needle.len() as u32),
+        )
+    }
+
+    #[test]
+    fn test_field_option_location_aggregate_resolved() {
+        assert_eq!(
+            resolved_field_diagnostic_span(
+                "tests.OptionLocationTestCase",
+                "aggregate",
+                "'ignore_above'"
+            ),
+            Some(span_of(line_of("string aggregate = 2"), "ignore_above: 0")),
+        );
+    }
+
+    #[test]
+    fn test_field_option_location_nested_aggregate_resolved() {
+        assert_eq!(
+            resolved_field_diagnostic_span(
+                "tests.OptionLocationTestCase",
+                "nested",
+                "'ignore_above'"
+            ),
+            Some(span_of(line_of("string nested = 4") + 3, "ignore_above: 0")),
+        );
+    }
+
+    /// Render the diagnostics for `message` with `parameter`, and whether each is an error.
+    fn lint_diagnostics(message: &str, parameter: &str) -> Vec<(String, bool)> {
+        let req = make_request_with_parameter("tests/tests.proto", parameter);
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        diagnostics
+            .iter()
+            .filter(|d| d.kind.to_string().starts_with(&format!("{message}.")))
+            .map(|d| (d.to_string(), d.is_error()))
+            .collect()
+    }
+
+    #[test]
+    fn test_allow_field() {
+        let diagnostics = lint_diagnostics("tests.AllowTestCase", "");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .contains("W001 tests.AllowTestCase.Reported")
+        );
+    }
+
+    #[test]
+    fn test_allow_message() {
+        assert_eq!(lint_diagnostics("tests.AllowMessageTestCase", ""), []);
+        // `allow` only configures the plugin.
+        let req = make_request("tests/tests.proto", None);
+        let (resp, _diagnostics) = crate::process(req).unwrap();
+        let output = output_for(&resp, "tests.AllowMessageTestCase");
+        assert!(
+            output
+                .values()
+                .all(|mapping| mapping.get("allow").is_none())
+        );
+        assert!(!output.is_empty());
+    }
+
+    #[test]
+    fn test_allow_invalid_code() {
+        let req = make_request("tests/tests.proto", None);
+        let (_resp, mut diagnostics) = crate::process(req).unwrap();
+        let include_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../proto");
+        crate::report::Sources::load(&[include_path], &diagnostics).resolve(&mut diagnostics);
+        let invalid: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.kind.to_string().starts_with("tests.AllowInvalidTestCase"))
+            .map(|d| (d.kind.to_string(), d.location.as_ref().and_then(|l| l.span)))
+            .collect();
+        let line = line_of("message AllowInvalidTestCase");
+        assert_eq!(
+            invalid,
+            [
+                (
+                    "tests.AllowInvalidTestCase: 'allow' 'E001' is not a warning code".to_string(),
+                    Some(span_of(
+                        line + 1,
+                        "option (protosearch.index).allow = \"E001\";"
+                    )),
+                ),
+                (
+                    "tests.AllowInvalidTestCase.title: 'allow' 'W1' is not a warning code"
+                        .to_string(),
+                    Some(span_of(line + 3, "allow: [\"W001\", \"W1\"]")),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_allow_parameter() {
+        assert_eq!(lint_diagnostics("tests.AllowTestCase", "allow=W001"), []);
+    }
+
+    #[test]
+    fn test_deny_parameter() {
+        let req = make_request_with_parameter("tests/tests.proto", "deny=W001");
+        let (resp, diagnostics) = crate::process(req).unwrap();
+        let denied = diagnostics
+            .iter()
+            .find(|d| {
+                d.kind
+                    .to_string()
+                    .starts_with("tests.AllowTestCase.Reported")
+            })
+            .unwrap();
+        assert!(denied.is_error());
+        assert_eq!(denied.code(), "W001");
+        // Errors abort compilation of the message.
+        assert!(output_for(&resp, "tests.AllowTestCase").is_empty());
+        // Allowed warnings are never denied.
+        assert_eq!(
+            lint_diagnostics("tests.AllowMessageTestCase", "deny=W001"),
+            []
+        );
+    }
+
+    #[test]
+    fn test_werror_parameter() {
+        let req = make_request_with_parameter("tests/tests.proto", "werror");
+        let (_resp, diagnostics) = crate::process(req).unwrap();
+        assert!(diagnostics.iter().all(|d| d.is_error()));
+        assert!(diagnostics.iter().any(|d| d.code().starts_with('W')));
+    }
+
+    #[test]
+    fn test_conflicting_vendor_properties() {
+        let req = make_request("tests/tests.proto", None);
+        let (resp, diagnostics) = crate::process(req).unwrap();
+        let diagnostic = diagnostics
+            .iter()
+            .find(|d| matches!(d.kind, DiagnosticKind::ConflictingVendorProperties { .. }))
+            .unwrap();
+        assert_eq!(
+            diagnostic.kind,
+            DiagnosticKind::ConflictingVendorProperties {
+                message: "ConflictingVendorTestCase".to_string(),
+                field: "title".to_string(),
+                vendor: "vendor".to_string(),
+                types: vec!["keyword".to_string(), "text".to_string()],
+            }
+        );
+        assert_eq!(
+            diagnostic
+                .location
+                .as_ref()
+                .and_then(|l| l.span)
+                .map(|s| s.start.line),
+            Some(line_of("(tests.vendor.text).analyzer = \"standard\""))
+        );
+        assert!(output_for(&resp, "tests.ConflictingVendorTestCase").is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_vendor_mapping_properties() {
+        let req = make_request("tests/tests.proto", None);
+        let (resp, diagnostics) = crate::process(req).unwrap();
+        let conflicts: Vec<_> = diagnostics
+            .iter()
+            .filter_map(|d| match &d.kind {
+                DiagnosticKind::ConflictingVendorProperties { message, field, .. }
+                    if message == "ConflictingVendorMappingTestCase" =>
+                {
+                    let line = d
+                        .location
+                        .as_ref()
+                        .and_then(|l| l.span)
+                        .map(|s| s.start.line);
+                    Some((field.as_str(), line))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            conflicts,
+            [
+                (
+                    "title",
+                    Some(line_of(
+                        "(protosearch.field).mapping.vendor.(tests.vendor.text).analyzer"
+                    ))
+                ),
+                ("body.raw", Some(line_of("string body = 2"))),
+            ]
+        );
+        assert!(output_for(&resp, "tests.ConflictingVendorMappingTestCase").is_empty());
+    }
+
+    #[test]
+    fn test_vendor_library() {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let proto_dir = manifest_dir.join("../../proto");
+        let vendor_dir = manifest_dir.join("../protosearch-vendor/proto");
+        let out = PathBuf::from(env!("OUT_DIR")).join("vendor_library.pb");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("cannot find bundled protoc");
+        let status = std::process::Command::new(&protoc)
+            .arg("-I")
+            .arg(&proto_dir)
+            .arg("-I")
+            .arg(&vendor_dir)
+            .arg("--include_imports")
+            .arg("--include_source_info")
+            .arg("--descriptor_set_out")
+            .arg(&out)
+            .arg("tests/vendor_library.proto")
+            .status()
+            .expect("failed to execute protoc");
+        assert!(status.success(), "protoc failed with status {status}");
+        let bytes = std::fs::read(&out).unwrap();
+        let mut req = CodeGeneratorRequest::new();
+        req.set_parameter("target=elasticsearch".to_string());
+        req.file_to_generate
+            .push("tests/vendor_library.proto".to_string());
+        req.proto_file = FileDescriptorSet::parse_from_bytes(&bytes).unwrap().file;
+        let (resp, diagnostics) = crate::process(req).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        insta::assert_json_snapshot!(output_for(
+            &resp,
+            "tests.vendor_library.ElasticsearchTestCase"
+        ));
+    }
+
+    /// Generate a vendor library from the OpenSearch specification fixture in `dir`, like the
+    /// `protosearch-vendor` pipeline.
+    fn generate_vendor_fixture(dir: &std::path::Path) -> PathBuf {
+        let spec = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../protosearch-vendor/spec/tests/opensearch.json");
+        let openapi = serde_json::from_str(&std::fs::read_to_string(spec).unwrap()).unwrap();
+        let spec = protosearch_vendor::extract(&openapi).unwrap();
+        let mut file = protosearch_vendor::proto::File::new("tests.opensearch");
+        // Clear of the extension numbers of the bundled libraries.
+        protosearch_vendor::compile_into(&spec, Some(&mut file), 3000).unwrap();
+        let path = dir.join("tests/opensearch.proto");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut out = std::fs::File::create(&path).unwrap();
+        protosearch_vendor::render(&mut out, &file).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_vendor_protos_compile() {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let proto_dir = manifest_dir.join("../../proto");
+        let vendor_dir = manifest_dir.join("../protosearch-vendor/proto");
+        let generated_dir = PathBuf::from(env!("OUT_DIR")).join("generated");
+        let mut protos: Vec<PathBuf> = std::fs::read_dir(vendor_dir.join("protosearch"))
+            .unwrap()
+            .flat_map(|entry| std::fs::read_dir(entry.unwrap().path()).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+            .collect();
+        protos.sort();
+        assert!(!protos.is_empty());
+        protos.push(generate_vendor_fixture(&generated_dir));
+        // Compile the vendor libraries together so their extension numbers cannot collide.
+        let out = PathBuf::from(env!("OUT_DIR")).join("vendor.pb");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("cannot find bundled protoc");
+        let output = std::process::Command::new(&protoc)
+            .arg("-I")
+            .arg(&proto_dir)
+            .arg("-I")
+            .arg(&vendor_dir)
+            .arg("-I")
+            .arg(&generated_dir)
+            .arg("--include_imports")
+            .arg("--descriptor_set_out")
+            .arg(&out)
+            .args(&protos)
+            .output()
+            .expect("failed to execute protoc");
+        assert!(
+            output.status.success(),
+            "protoc failed with status {}:\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        // Each library extends `MappingOptions` with typed properties that the plugin finds.
+        let bytes = std::fs::read(&out).unwrap();
+        let files = protobuf::reflect::FileDescriptor::new_dynamic_fds(
+            FileDescriptorSet::parse_from_bytes(&bytes).unwrap().file,
+            &[],
+        )
+        .unwrap();
+        for proto in &protos {
+            let file = files
+                .iter()
+                .find(|f| proto.ends_with(f.name()))
+                .unwrap_or_else(|| panic!("missing descriptor for {}", proto.display()));
+            assert!(
+                !crate::vendor::find_vendor_properties([file]).is_empty(),
+                "{} does not extend protosearch.MappingOptions",
+                file.name()
+            );
+        }
+    }
+
     #[test]
     fn test_missing_descriptor() {
         let req = make_request("missing.proto", None);
@@ -1,24 +1,38 @@
 use std::io::{Read, Write};
 
 use protobuf::Message;
+use protosearch_plugin::Config;
+use protosearch_plugin::report::{self, Sources};
 
 fn main() -> protosearch_plugin::Result<()> {
     let mut buf = Vec::new();
     std::io::stdin().read_to_end(&mut buf)?;
     let req = protobuf::plugin::CodeGeneratorRequest::parse_from_bytes(&buf)?;
-    let (mut resp, diagnostics) = protosearch_plugin::process(req)?;
-    let (errors, warnings): (Vec<_>, Vec<_>) = diagnostics.iter().partition(|d| d.is_error());
+    let config = Config::try_from(req.parameter())?;
+    let (mut resp, mut diagnostics) = protosearch_plugin::process(req)?;
+    let sources = Sources::load(&config.include_paths, &diagnostics);
+    sources.resolve(&mut diagnostics);
+    // `protoc` prints errors in a format for people.
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .cloned()
+        .collect();
     if !errors.is_empty() {
-        resp.set_error(
-            errors
-                .iter()
-                .map(|d| d.to_string())
-                .collect::<Vec<_>>()
-                .join("\n"),
-        );
+        resp.set_error(report::render(
+            report::text_format(config.diagnostics),
+            &errors,
+            &sources,
+        )?);
     }
-    for w in &warnings {
-        eprintln!("{w}");
+    if let Some(path) = &config.diagnostics_path {
+        std::fs::write(
+            path,
+            report::render(config.diagnostics, &diagnostics, &sources)?,
+        )?;
+    }
+    if let Some(stderr) = report::render_stderr(&config, &diagnostics, &sources)? {
+        eprintln!("{stderr}");
     }
     let out = resp.write_to_bytes()?;
     std::io::stdout().write_all(&out)?;
@@ -1,21 +1,30 @@
 use std::collections::BTreeMap;
 use std::fmt;
 
-use protobuf::reflect::{MessageDescriptor, ReflectFieldRef, ReflectValueRef};
+use protobuf::reflect::{FieldDescriptor, MessageDescriptor, ReflectFieldRef, ReflectValueRef};
 use protobuf::{Enum, MessageDyn};
 use serde::Serialize;
 use serde::ser::{Error, Serializer};
 use serde_json::{Map, Value, json};
 
 use crate::Result;
-use crate::proto::{Dynamic, FieldMapping, Index, IndexOptions, SourceMode, TermVector};
+use crate::proto::{
+    Dynamic, DynamicTemplate, FieldMapping, Index, IndexMode, IndexOptions, MatchPattern,
+    SourceMode, TermVector,
+};
 
 /// A document mapping.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct Mapping {
     pub descriptor: Option<MessageDescriptor>,
-    pub index: Option<Index>,
+    pub index: Option<Box<Index>>,
     pub properties: BTreeMap<String, Property>,
+    /// The protobuf fields that compile to each property, in declaration order.
+    pub fields: BTreeMap<String, Vec<FieldDescriptor>>,
+    /// Dynamic templates generated for map fields, relative to this mapping.
+    pub map_templates: Vec<MapTemplate>,
+    /// Oneof groups to record in `_meta`, i.e., the paths of their members keyed by group path.
+    pub oneofs: BTreeMap<String, Vec<String>>,
 }
 
 /// A mapping property.
@@ -30,6 +39,47 @@ pub enum Property {
     },
 }
 
+/// A dynamic template generated for the values of a map field.
+#[derive(Debug)]
+pub struct MapTemplate {
+    pub path_match: String,
+    pub parameters: Parameters,
+}
+
+/// A create index request body, i.e., `settings`, `mappings`, and `aliases`.
+#[derive(Debug)]
+pub struct CreateIndex<'a>(pub &'a Mapping);
+
+/// A composable index template body.
+#[derive(Debug)]
+pub struct ComposableTemplate<'a>(pub &'a Mapping);
+
+/// A component template body.
+#[derive(Debug)]
+pub struct ComponentTemplate<'a>(pub &'a Mapping);
+
+/// A legacy index template body.
+#[derive(Debug)]
+pub struct LegacyTemplate<'a>(pub &'a Mapping);
+
+/// [`Index`] fields that a create index request body sets alongside `mappings`.
+const CREATE_INDEX_FIELDS: &[&str] = &["aliases", "settings"];
+
+/// [`Index`] fields that configure index templates.
+const TEMPLATE_FIELD: &str = "template";
+
+/// [`Index`] fields that only configure the plugin.
+const PLUGIN_FIELDS: &[&str] = &["allow"];
+
+/// [`FieldMapping`](crate::proto::FieldMapping) fields that only configure the plugin.
+pub const FIELD_MAPPING_PLUGIN_FIELDS: &[&str] = &["vendor"];
+
+/// [`IndexTemplate`](crate::proto::IndexTemplate) fields that component templates support.
+const COMPONENT_TEMPLATE_FIELDS: &[&str] = &["_meta", "version"];
+
+/// [`IndexTemplate`](crate::proto::IndexTemplate) fields that legacy templates support.
+const LEGACY_TEMPLATE_FIELDS: &[&str] = &["index_patterns", "priority", "version"];
+
 #[derive(Debug)]
 pub enum Parameters {
     Typed {
@@ -45,6 +95,150 @@ impl Mapping {
             descriptor: Some(descriptor),
             index: None,
             properties: Default::default(),
+            fields: Default::default(),
+            map_templates: Default::default(),
+            oneofs: Default::default(),
+        }
+    }
+
+    /// Move the properties, fields, dynamic templates, and oneof groups of `other` to this mapping.
+    pub fn append(&mut self, other: &mut Mapping) {
+        self.properties.append(&mut other.properties);
+        for (name, mut fields) in std::mem::take(&mut other.fields) {
+            self.fields.entry(name).or_default().append(&mut fields);
+        }
+        self.map_templates.append(&mut other.map_templates);
+        self.oneofs.append(&mut other.oneofs);
+    }
+
+    /// Remove the property `name`, and the dynamic templates and oneof groups under it, into a
+    /// mapping of their own.
+    pub fn split_off(&mut self, name: &str) -> Mapping {
+        let prefix = format!("{name}.");
+        let mut split = Mapping::default();
+        if let Some(property) = self.properties.remove(name) {
+            split.properties.insert(name.to_string(), property);
+        }
+        if let Some(fields) = self.fields.remove(name) {
+            split.fields.insert(name.to_string(), fields);
+        }
+        (split.map_templates, self.map_templates) = std::mem::take(&mut self.map_templates)
+            .into_iter()
+            .partition(|template| template.path_match.starts_with(&prefix));
+        (split.oneofs, self.oneofs) = std::mem::take(&mut self.oneofs)
+            .into_iter()
+            .partition(|(group, _)| group.starts_with(&prefix));
+        split
+    }
+
+    /// Insert a property, hoisting the dynamic templates and oneof groups of an object property
+    /// to this mapping.
+    pub fn insert(&mut self, name: String, mut property: Property) {
+        if let Property::Object { properties, .. } = &mut property {
+            self.map_templates.extend(
+                properties
+                    .map_templates
+                    .drain(..)
+                    .map(|template| template.under(&name)),
+            );
+            self.oneofs
+                .extend(std::mem::take(&mut properties.oneofs).into_iter().map(
+                    |(group, members)| {
+                        (
+                            format!("{name}.{group}"),
+                            members.iter().map(|m| format!("{name}.{m}")).collect(),
+                        )
+                    },
+                ));
+        }
+        self.properties.insert(name, property);
+    }
+
+    /// Find the protobuf field that compiles to the property at a dotted `path`.
+    pub fn field(&self, path: &str) -> Option<&FieldDescriptor> {
+        if let Some(fields) = self.fields.get(path) {
+            return fields.first();
+        }
+        path.match_indices('.')
+            .find_map(|(i, _)| match self.properties.get(&path[..i]) {
+                Some(Property::Object { properties, .. }) => properties.field(&path[i + 1..]),
+                _ => None,
+            })
+    }
+
+    /// Find the property at a dotted `path`, e.g. `author.name`.
+    ///
+    /// Property names may contain dots themselves, so this tries every way to split `path`.
+    pub fn get(&self, path: &str) -> Option<&Property> {
+        if let Some(property) = self.properties.get(path) {
+            return Some(property);
+        }
+        path.match_indices('.')
+            .find_map(|(i, _)| match self.properties.get(&path[..i]) {
+                Some(Property::Object { properties, .. }) => properties.get(&path[i + 1..]),
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Debug for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `FieldDescriptor` does not implement `Debug`.
+        let fields: BTreeMap<&str, Vec<String>> = self
+            .fields
+            .iter()
+            .map(|(name, fields)| {
+                (
+                    name.as_str(),
+                    fields.iter().map(|f| f.full_name().to_string()).collect(),
+                )
+            })
+            .collect();
+        f.debug_struct("Mapping")
+            .field("descriptor", &self.descriptor)
+            .field("index", &self.index)
+            .field("properties", &self.properties)
+            .field("fields", &fields)
+            .field("map_templates", &self.map_templates)
+            .field("oneofs", &self.oneofs)
+            .finish()
+    }
+}
+
+impl Property {
+    /// The effective `type` of the property, if known.
+    pub fn type_(&self) -> Option<&str> {
+        match self {
+            Self::Leaf(parameters) | Self::Object { parameters, .. } => parameters.type_(),
+        }
+    }
+}
+
+impl Parameters {
+    /// The explicit or inferred `type`, if known.
+    pub fn type_(&self) -> Option<&str> {
+        match self {
+            Self::Typed {
+                field_mapping,
+                inferred_type,
+            } => {
+                if field_mapping.has_type() {
+                    Some(field_mapping.type_())
+                } else {
+                    inferred_type.as_deref()
+                }
+            }
+            Self::Raw(params) => params.get("type").and_then(Value::as_str),
+        }
+    }
+}
+
+impl MapTemplate {
+    /// Make the template relative to the parent of the property `name`.
+    pub fn under(self, name: &str) -> Self {
+        Self {
+            path_match: format!("{name}.{}", self.path_match),
+            parameters: self.parameters,
         }
     }
 }
@@ -54,15 +248,38 @@ impl Serialize for Mapping {
     where
         S: Serializer,
     {
-        let mut map: BTreeMap<String, Value> = self
-            .index
-            .as_ref()
-            .map(|i| other_to_json(i as &dyn MessageDyn))
-            .transpose()
+        let mut map: BTreeMap<String, Value> = index_to_json(self.index.as_deref())
             .map_err(S::Error::custom)?
-            .unwrap_or_default()
             .into_iter()
+            .filter(|(k, _)| !is_index_field(k) && !PLUGIN_FIELDS.contains(&k.as_str()))
             .collect();
+        if !self.map_templates.is_empty() {
+            let generated = self
+                .map_templates
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(S::Error::custom)?;
+            // Declared templates take precedence over generated ones.
+            match map
+                .entry("dynamic_templates".to_string())
+                .or_insert_with(|| Value::Array(Vec::new()))
+            {
+                Value::Array(templates) => templates.extend(generated),
+                _ => unreachable!("dynamic_templates is always an array"),
+            }
+        }
+        if !self.oneofs.is_empty() {
+            match map
+                .entry("_meta".to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                Value::Object(meta) => {
+                    meta.insert("oneof".to_string(), json!(self.oneofs));
+                }
+                _ => unreachable!("_meta is always an object"),
+            }
+        }
         if !self.properties.is_empty() {
             map.insert(
                 "properties".to_string(),
@@ -73,6 +290,79 @@ impl Serialize for Mapping {
     }
 }
 
+impl Serialize for CreateIndex<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        create_index_to_map(self.0)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl Serialize for ComposableTemplate<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = template_to_map(self.0).map_err(S::Error::custom)?;
+        map.insert(
+            "template".to_string(),
+            serde_json::to_value(create_index_to_map(self.0).map_err(S::Error::custom)?)
+                .map_err(S::Error::custom)?,
+        );
+        map.serialize(serializer)
+    }
+}
+
+impl Serialize for ComponentTemplate<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = template_to_map(self.0).map_err(S::Error::custom)?;
+        map.retain(|k, _| COMPONENT_TEMPLATE_FIELDS.contains(&k.as_str()));
+        map.insert(
+            "template".to_string(),
+            serde_json::to_value(create_index_to_map(self.0).map_err(S::Error::custom)?)
+                .map_err(S::Error::custom)?,
+        );
+        map.serialize(serializer)
+    }
+}
+
+impl Serialize for LegacyTemplate<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = template_to_map(self.0).map_err(S::Error::custom)?;
+        map.retain(|k, _| LEGACY_TEMPLATE_FIELDS.contains(&k.as_str()));
+        // Legacy templates call `priority` `order`.
+        if let Some(priority) = map.remove("priority") {
+            map.insert("order".to_string(), priority);
+        }
+        map.extend(create_index_to_map(self.0).map_err(S::Error::custom)?);
+        map.serialize(serializer)
+    }
+}
+
+impl Serialize for MapTemplate {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        json!({
+            &self.path_match: {
+                "path_match": &self.path_match,
+                "mapping": &self.parameters,
+            }
+        })
+        .serialize(serializer)
+    }
+}
+
 impl Serialize for Property {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -85,10 +375,13 @@ impl Serialize for Property {
                 properties,
             } => {
                 let mut map = parameters_to_map(parameters).map_err(S::Error::custom)?;
-                map.insert(
-                    "properties".to_string(),
-                    serde_json::to_value(&properties.properties).map_err(S::Error::custom)?,
-                );
+                // Map fields may only generate dynamic templates.
+                if !properties.properties.is_empty() {
+                    map.insert(
+                        "properties".to_string(),
+                        serde_json::to_value(&properties.properties).map_err(S::Error::custom)?,
+                    );
+                }
                 map.serialize(serializer)
             }
         }
@@ -114,7 +407,7 @@ fn parameters_to_map(parameters: &Parameters) -> Result<BTreeMap<String, Value>>
             inferred_type,
         } => {
             let mut map: BTreeMap<String, Value> =
-                other_to_json(field_mapping.as_ref() as &dyn MessageDyn)?
+                field_mapping_to_json(field_mapping.as_ref() as &dyn MessageDyn)?
                     .into_iter()
                     .collect();
             if let Some(t) = inferred_type {
@@ -150,6 +443,27 @@ impl fmt::Display for IndexOptions {
     }
 }
 
+impl fmt::Display for IndexMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::INDEX_MODE_UNSPECIFIED => "",
+            Self::INDEX_MODE_STANDARD => "standard",
+            Self::INDEX_MODE_TIME_SERIES => "time_series",
+            Self::INDEX_MODE_LOGSDB => "logsdb",
+        })
+    }
+}
+
+impl fmt::Display for MatchPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MATCH_PATTERN_UNSPECIFIED => "",
+            Self::MATCH_PATTERN_SIMPLE => "simple",
+            Self::MATCH_PATTERN_REGEX => "regex",
+        })
+    }
+}
+
 impl fmt::Display for SourceMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -176,10 +490,56 @@ impl fmt::Display for TermVector {
     }
 }
 
-fn to_json(message: &dyn MessageDyn) -> Result<Value> {
+/// Return whether the [`Index`] field `name` configures the index itself rather than its mapping.
+fn is_index_field(name: &str) -> bool {
+    CREATE_INDEX_FIELDS.contains(&name) || name == TEMPLATE_FIELD
+}
+
+/// Build a create index request body from `mapping`.
+fn create_index_to_map(mapping: &Mapping) -> Result<BTreeMap<String, Value>> {
+    let mut map: BTreeMap<String, Value> = index_to_json(mapping.index.as_deref())?
+        .into_iter()
+        .filter(|(k, _)| CREATE_INDEX_FIELDS.contains(&k.as_str()))
+        .collect();
+    map.insert("mappings".to_string(), serde_json::to_value(mapping)?);
+    Ok(map)
+}
+
+/// Extract the template parameters from `mapping`.
+fn template_to_map(mapping: &Mapping) -> Result<BTreeMap<String, Value>> {
+    Ok(mapping
+        .index
+        .as_ref()
+        .and_then(|i| i.template.as_ref())
+        .map(|t| other_to_json(t as &dyn MessageDyn))
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .collect())
+}
+
+fn index_to_json(index: Option<&Index>) -> Result<Map<String, Value>> {
+    index
+        .map(|i| other_to_json(i as &dyn MessageDyn))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+pub fn to_json(message: &dyn MessageDyn) -> Result<Value> {
     match message.descriptor_dyn().full_name() {
         "google.protobuf.Value" => wkt_value_to_json(message),
         "google.protobuf.ListValue" => list_value_to_json(message),
+        "google.protobuf.Struct" => struct_to_json(message),
+        "protosearch.Analyzer"
+        | "protosearch.CharFilter"
+        | "protosearch.TokenFilter"
+        | "protosearch.Tokenizer" => analysis_component_to_json(message),
+        "protosearch.FieldMapping" => Ok(Value::Object(field_mapping_to_json(message)?)),
+        "protosearch.DynamicTemplate" => dynamic_template_to_json(
+            message
+                .downcast_ref::<DynamicTemplate>()
+                .expect("protosearch.DynamicTemplate is a generated message"),
+        ),
         _ => Ok(Value::Object(other_to_json(message)?)),
     }
 }
@@ -197,7 +557,9 @@ fn reflect_value_to_json(v: ReflectValueRef) -> Result<Value> {
         ReflectValueRef::Bytes(b) => Ok(json!(b)),
         ReflectValueRef::Enum(desc, i) => match desc.full_name() {
             "protosearch.Dynamic" => proto_enum_to_json::<Dynamic>(i),
+            "protosearch.IndexMode" => proto_enum_to_json::<IndexMode>(i),
             "protosearch.IndexOptions" => proto_enum_to_json::<IndexOptions>(i),
+            "protosearch.MatchPattern" => proto_enum_to_json::<MatchPattern>(i),
             "protosearch.SourceMode" => proto_enum_to_json::<SourceMode>(i),
             "protosearch.TermVector" => proto_enum_to_json::<TermVector>(i),
             _ => unreachable!(
@@ -209,6 +571,20 @@ fn reflect_value_to_json(v: ReflectValueRef) -> Result<Value> {
     }
 }
 
+/// Convert a `google.protobuf.Value` or vendor parameter number to JSON.
+///
+/// Like the canonical JSON encoding, write integral numbers without a fraction. Elasticsearch
+/// rejects `3.0` for integer parameters such as `min_gram`, which vendor libraries and
+/// `google.protobuf.Value` can only represent as `double`. Typed `double` fields such as `boost`
+/// keep their fraction.
+pub fn number_to_json(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < (1u64 << 53) as f64 {
+        json!(n as i64)
+    } else {
+        json!(n)
+    }
+}
+
 fn wkt_value_to_json(msg: &dyn MessageDyn) -> Result<Value> {
     let desc = msg.descriptor_dyn();
     let oneof = desc
@@ -218,8 +594,9 @@ fn wkt_value_to_json(msg: &dyn MessageDyn) -> Result<Value> {
         if let ReflectFieldRef::Optional(v) = field.get_reflect(msg)
             && let Some(rv) = v.value()
         {
-            return match field.name() {
-                "null_value" => Ok(Value::Null),
+            return match (field.name(), rv.to_f64()) {
+                ("null_value", _) => Ok(Value::Null),
+                ("number_value", Some(n)) => Ok(number_to_json(n)),
                 _ => reflect_value_to_json(rv),
             };
         }
@@ -242,6 +619,71 @@ fn list_value_to_json(msg: &dyn MessageDyn) -> Result<Value> {
     }
 }
 
+fn struct_to_json(msg: &dyn MessageDyn) -> Result<Value> {
+    let desc = msg.descriptor_dyn();
+    let fields_field = desc
+        .field_by_name("fields")
+        .expect("google.protobuf.Struct must have a 'fields' field");
+    match fields_field.get_reflect(msg) {
+        ReflectFieldRef::Map(m) => {
+            let mut obj = Map::new();
+            for (k, v) in m.into_iter() {
+                let ReflectValueRef::String(s) = k else {
+                    unreachable!("google.protobuf.Struct fields have string keys")
+                };
+                obj.insert(s.to_string(), reflect_value_to_json(v)?);
+            }
+            Ok(Value::Object(obj))
+        }
+        _ => unreachable!("google.protobuf.Struct fields is always a map"),
+    }
+}
+
+/// Convert an analysis component to JSON, merging `params` into the definition.
+///
+/// Explicit fields take precedence over `params`.
+fn analysis_component_to_json(msg: &dyn MessageDyn) -> Result<Value> {
+    let mut map = other_to_json(msg)?;
+    if let Some(Value::Object(params)) = map.remove("params") {
+        for (k, v) in params {
+            map.entry(k).or_insert(v);
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+/// Convert a dynamic template to a single-entry JSON object keyed by its name.
+fn dynamic_template_to_json(template: &DynamicTemplate) -> Result<Value> {
+    const CONDITIONS: &[&str] = &[
+        "match",
+        "unmatch",
+        "path_match",
+        "path_unmatch",
+        "match_mapping_type",
+    ];
+    let mut map = other_to_json(template)?;
+    map.remove("name");
+    for condition in CONDITIONS {
+        if let Some(Value::Array(patterns)) = map.get_mut(*condition)
+            && patterns.len() == 1
+        {
+            let pattern = patterns.remove(0);
+            map.insert(condition.to_string(), pattern);
+        }
+    }
+    if let Some(Value::String(s)) = map.remove("json") {
+        map.insert("mapping".to_string(), serde_json::from_str(&s)?);
+    }
+    Ok(json!({ template.name(): map }))
+}
+
+/// Convert a field mapping, including its multi-fields, to mapping parameters.
+fn field_mapping_to_json(msg: &dyn MessageDyn) -> Result<Map<String, Value>> {
+    let mut map = other_to_json(msg)?;
+    map.retain(|k, _| !FIELD_MAPPING_PLUGIN_FIELDS.contains(&k.as_str()));
+    Ok(map)
+}
+
 fn other_to_json(msg: &dyn MessageDyn) -> Result<Map<String, Value>> {
     let desc = msg.descriptor_dyn();
     let mut map = Map::new();
@@ -1,5 +1,7 @@
-use protobuf::UnknownValueRef;
-use protobuf::reflect::{FieldDescriptor, MessageDescriptor};
+use protobuf::reflect::{
+    FieldDescriptor, MessageDescriptor, OneofDescriptor, RuntimeFieldType, RuntimeType,
+};
+use protobuf::{Message, UnknownFields, UnknownValueRef};
 
 use crate::{Result, proto};
 
@@ -9,37 +11,50 @@ pub const EXTENSION_NUMBER: u32 = 50_000;
 ///
 /// This inspects unknown fields because `protobuf` 3.x does not support an extension registry.
 pub fn get_field_options(field: &FieldDescriptor) -> Result<Option<proto::Field>> {
-    use protobuf::Message;
-    let field_proto = field.proto();
-    let unknown_fields = field_proto.options.special_fields.unknown_fields();
-    let mut field = proto::Field::new();
-    let mut found = false;
-    for (number, val) in unknown_fields.iter() {
-        if number == EXTENSION_NUMBER
-            && let UnknownValueRef::LengthDelimited(b) = val
-        {
-            field.merge_from_bytes(b)?;
-            found = true;
-        }
-    }
-    Ok(if found { Some(field) } else { None })
+    get_extension(field.proto().options.special_fields.unknown_fields())
 }
 
 pub fn get_index_options(message: &MessageDescriptor) -> Result<Option<proto::Index>> {
-    use protobuf::Message;
-    let message_proto = message.proto();
-    let unknown_fields = message_proto.options.special_fields.unknown_fields();
-    let mut index = proto::Index::new();
+    get_extension(message.proto().options.special_fields.unknown_fields())
+}
+
+pub fn get_oneof_options(oneof: &OneofDescriptor) -> Result<Option<proto::Oneof>> {
+    get_extension(oneof.proto().options.special_fields.unknown_fields())
+}
+
+/// Decode the protosearch extension from the unknown fields of an options message.
+fn get_extension<M: Message>(unknown_fields: &UnknownFields) -> Result<Option<M>> {
+    let mut options = M::new();
     let mut found = false;
     for (number, val) in unknown_fields.iter() {
         if number == EXTENSION_NUMBER
             && let UnknownValueRef::LengthDelimited(b) = val
         {
-            index.merge_from_bytes(b)?;
+            options.merge_from_bytes(b)?;
             found = true;
         }
     }
-    Ok(if found { Some(index) } else { None })
+    Ok(if found { Some(options) } else { None })
+}
+
+/// Convert a path of field names in the options message `descriptor` to field numbers, e.g.,
+/// `["mapping", "ignore_above"]` in [`proto::Field`] to `[3, 12]`.
+///
+/// Stops at the first name that is not a field.
+pub fn option_field_numbers(descriptor: MessageDescriptor, names: &[&str]) -> Vec<i32> {
+    let mut numbers = Vec::new();
+    let mut descriptor = Some(descriptor);
+    for name in names {
+        let Some(field) = descriptor.and_then(|d| d.field_by_name(name)) else {
+            break;
+        };
+        numbers.push(field.number());
+        descriptor = match field.runtime_field_type() {
+            RuntimeFieldType::Singular(RuntimeType::Message(d)) => Some(d),
+            _ => None,
+        };
+    }
+    numbers
 }
 
 /// Return `name` if specified, otherwise the field name.
@@ -0,0 +1,27 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::LazyLock;
+
+/// The parameters supported by each field type.
+///
+/// `parameters.json` is generated from the Elasticsearch specification by `protosearch-vendor`.
+static PARAMETERS: LazyLock<BTreeMap<String, BTreeSet<String>>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("parameters.json")).expect("parameters.json is valid")
+});
+
+/// Parameters the specification declares on every property, but which only apply to some types.
+const OVERRIDES: &[(&str, &[&str])] = &[
+    ("dynamic", &["nested", "object", "passthrough"]),
+    (
+        "ignore_above",
+        &["flattened", "icu_collation_keyword", "keyword", "wildcard"],
+    ),
+];
+
+/// Return whether `parameter` applies to fields of type `type_name`, or `None` if the type is unknown.
+pub fn applies(type_name: &str, parameter: &str) -> Option<bool> {
+    let parameters = PARAMETERS.get(type_name)?;
+    if let Some((_, types)) = OVERRIDES.iter().find(|(name, _)| *name == parameter) {
+        return Some(types.contains(&type_name));
+    }
+    Some(parameters.contains(parameter))
+}
@@ -1,15 +1,29 @@
+use std::collections::BTreeMap;
+
+use protobuf::MessageFull;
 use protobuf::plugin::{
     CodeGeneratorRequest, CodeGeneratorResponse,
     code_generator_response::{Feature, File},
 };
-use protobuf::reflect::{FieldDescriptor, MessageDescriptor, RuntimeFieldType, RuntimeType};
-use serde_json::Value;
+use protobuf::reflect::{
+    FieldDescriptor, FileDescriptor, MessageDescriptor, OneofDescriptor, RuntimeFieldType,
+    RuntimeType,
+};
+use serde_json::{Map, Value};
 
+use crate::config::{Output, is_warning_code};
 use crate::context::Context;
-use crate::diagnostic::{Diagnostic, DiagnosticKind, Location};
-use crate::mapping::{Mapping, Parameters, Property};
-use crate::options::{get_field_options, get_index_options, property_name};
-use crate::validator::{ValidationContext, validate};
+use crate::diagnostic::{Diagnostic, DiagnosticKind, Location, Severity};
+use crate::mapping::{
+    ComponentTemplate, ComposableTemplate, CreateIndex, LegacyTemplate, MapTemplate, Mapping,
+    Parameters, Property,
+};
+use crate::options::{
+    get_field_options, get_index_options, get_oneof_options, option_field_numbers, property_name,
+};
+use crate::proto::{Dynamic, MapStrategy, OneofStrategy, Recursion};
+use crate::validator::{ValidationContext, field_option_location, validate};
+use crate::vendor::{VendorProperty, decode_vendor_properties};
 use crate::{Error, Result, Span, proto};
 
 pub fn process(request: CodeGeneratorRequest) -> Result<(CodeGeneratorResponse, Vec<Diagnostic>)> {
@@ -17,27 +31,59 @@ pub fn process(request: CodeGeneratorRequest) -> Result<(CodeGeneratorResponse,
     response.set_supported_features(Feature::FEATURE_PROTO3_OPTIONAL as u64);
     let ctx = Context::try_from(request)?;
     let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    // The content of the component templates split from documents so far, keyed by name.
+    let mut components: BTreeMap<String, String> = BTreeMap::new();
     for filename in &ctx.files_to_generate {
         let file_descriptor =
             ctx.get_file_descriptor_by_name(filename)
                 .ok_or(Error::InvalidRequest(format!(
                     "missing descriptor for {filename}"
                 )))?;
+        let allowed = allowed_warnings(file_descriptor, &mut diagnostics)?;
         for message_descriptor in file_descriptor.messages() {
-            let validation_ctx = ValidationContext::new(filename, &message_descriptor);
             let mut message_diagnostics: Vec<Diagnostic> = Vec::new();
-            let mapping = compile_message(
+            let mut mapping = compile_message(
                 &ctx,
                 &message_descriptor,
                 filename,
+                &mut Vec::new(),
                 &mut message_diagnostics,
             )?;
+            let validation_ctx = ValidationContext::new(filename, &message_descriptor)
+                .with_index(mapping.index.as_deref());
             message_diagnostics.extend(validate(&validation_ctx, &mapping));
+            let message_diagnostics = apply_lints(&ctx, filename, &allowed, message_diagnostics);
             let has_errors = message_diagnostics.iter().any(|d| d.is_error());
             if (!mapping.properties.is_empty() || mapping.index.is_some()) && !has_errors {
+                if ctx.split_components()
+                    && matches!(
+                        ctx.output(),
+                        Output::IndexTemplate | Output::ComponentTemplate
+                    )
+                {
+                    for (name, content) in split_components(&mut mapping, &components)? {
+                        if ctx.output() == Output::ComponentTemplate
+                            && !components.contains_key(&name)
+                        {
+                            let mut file = File::new();
+                            file.set_name(format!("{name}.json"));
+                            file.set_content(content.clone());
+                            response.file.push(file);
+                        }
+                        components.insert(name, content);
+                    }
+                }
                 let mut file = File::new();
                 file.set_name(format!("{}.json", message_descriptor.full_name()));
-                file.set_content(serde_json::to_string(&mapping)?);
+                file.set_content(match ctx.output() {
+                    Output::Mapping => serde_json::to_string(&mapping)?,
+                    Output::Index => serde_json::to_string(&CreateIndex(&mapping))?,
+                    Output::IndexTemplate => serde_json::to_string(&ComposableTemplate(&mapping))?,
+                    Output::ComponentTemplate => {
+                        serde_json::to_string(&ComponentTemplate(&mapping))?
+                    }
+                    Output::LegacyTemplate => serde_json::to_string(&LegacyTemplate(&mapping))?,
+                });
                 response.file.push(file);
             }
             diagnostics.extend(message_diagnostics);
@@ -46,23 +92,383 @@ pub fn process(request: CodeGeneratorRequest) -> Result<(CodeGeneratorResponse,
     Ok((response, diagnostics))
 }
 
+/// Move the objects that message fields compile to at the root of `mapping` into component
+/// templates of their own, and add them to `composed_of`.
+///
+/// A component template is named after the message type and the property, e.g.,
+/// `tests.Author@author`, so documents can share it. An object stays in `mapping` if it compiles
+/// differently from a component template in `components` with the same name.
+fn split_components(
+    mapping: &mut Mapping,
+    components: &BTreeMap<String, String>,
+) -> Result<Vec<(String, String)>> {
+    let candidates: Vec<(String, String)> = mapping
+        .properties
+        .iter()
+        .filter(|(_, property)| matches!(property, Property::Object { .. }))
+        .filter_map(|(name, _)| {
+            let field = mapping.fields.get(name)?.last()?;
+            // Oneof objects and members belong with the rest of their group.
+            if field.containing_oneof().is_some_and(|o| !o.is_synthetic()) {
+                return None;
+            }
+            match field.runtime_field_type() {
+                RuntimeFieldType::Singular(RuntimeType::Message(desc))
+                | RuntimeFieldType::Repeated(RuntimeType::Message(desc)) => {
+                    Some((name.clone(), format!("{}@{name}", desc.full_name())))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    let mut split = Vec::new();
+    for (property, name) in candidates {
+        let mut component = mapping.split_off(&property);
+        let content = serde_json::to_string(&ComponentTemplate(&component))?;
+        if components.get(&name).is_some_and(|c| *c != content) {
+            mapping.append(&mut component);
+            continue;
+        }
+        split.push((name, content));
+    }
+    if !split.is_empty() {
+        mapping
+            .index
+            .get_or_insert_default()
+            .template
+            .mut_or_insert_default()
+            .composed_of
+            .extend(split.iter().map(|(name, _)| name.clone()));
+    }
+    Ok(split)
+}
+
+/// Collect the spans of the fields and messages in `file` that allow warnings, and the codes they
+/// allow.
+///
+/// Reports codes that are not warning codes, like the `allow` parameter.
+fn allowed_warnings(
+    file: &FileDescriptor,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<(Span, Vec<String>)>> {
+    let mut allowed = Vec::new();
+    let mut messages: Vec<MessageDescriptor> = file.messages().collect();
+    while let Some(message) = messages.pop() {
+        messages.extend(message.nested_messages());
+        if let Some(index) = get_index_options(&message)? {
+            let ctx = ValidationContext::new(file.name(), &message);
+            for (i, code) in index.allow.iter().enumerate() {
+                if !is_warning_code(code) {
+                    diagnostics.push(
+                        Diagnostic::error(DiagnosticKind::InvalidIndexOption {
+                            message: message.full_name().to_string(),
+                            option: "allow".to_string(),
+                            reason: format!("'{code}' is not a warning code"),
+                        })
+                        .at(ctx.index_item_location(&["allow"], i)),
+                    );
+                }
+            }
+            if let Some(span) = Span::from_message(&message)
+                && !index.allow.is_empty()
+            {
+                allowed.push((span, index.allow));
+            }
+        }
+        for field in message.fields() {
+            let Some(options) = get_field_options(&field)? else {
+                continue;
+            };
+            for (i, code) in options.allow.iter().enumerate() {
+                if !is_warning_code(code) {
+                    diagnostics.push(
+                        Diagnostic::error(DiagnosticKind::InvalidParameterValue {
+                            message: message.full_name().to_string(),
+                            field: field.name().to_string(),
+                            parameter: "allow".to_string(),
+                            reason: format!("'{code}' is not a warning code"),
+                        })
+                        .at(field_option_location(
+                            &field,
+                            &["allow"],
+                            Some(i),
+                        )),
+                    );
+                }
+            }
+            if let Some(span) = Span::from_field(&field)
+                && !options.allow.is_empty()
+            {
+                allowed.push((span, options.allow));
+            }
+        }
+    }
+    Ok(allowed)
+}
+
+/// Drop the warnings that `allowed` or the `allow` parameter suppress, and report the warnings
+/// that the `werror` or `deny` parameters deny as errors.
+///
+/// A warning is allowed if its location in `file` is within a field or message that allows it.
+fn apply_lints(
+    ctx: &Context,
+    file: &str,
+    allowed: &[(Span, Vec<String>)],
+    diagnostics: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter_map(|mut diagnostic| {
+            if diagnostic.is_error() {
+                return Some(diagnostic);
+            }
+            let code = diagnostic.code();
+            let span = diagnostic
+                .location
+                .as_ref()
+                .filter(|l| l.file == file)
+                .and_then(|l| l.span);
+            let allowed_here = span.is_some_and(|span| {
+                allowed
+                    .iter()
+                    .any(|(scope, codes)| scope.contains(&span) && codes.contains(&code))
+            });
+            if allowed_here || ctx.is_allowed(&code) {
+                return None;
+            }
+            if ctx.is_denied(&code) {
+                diagnostic.severity = Severity::Error;
+            }
+            Some(diagnostic)
+        })
+        .collect()
+}
+
 /// Compile a message as a document mapping.
+///
+/// `ancestors` holds the messages being compiled, outermost first.
 fn compile_message(
     ctx: &Context,
     message: &MessageDescriptor,
     file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Mapping> {
+    ancestors.push(message.clone());
+    let mapping = compile_message_fields(ctx, message, file, ancestors, diagnostics);
+    ancestors.pop();
+    mapping
+}
+
+fn compile_message_fields(
+    ctx: &Context,
+    message: &MessageDescriptor,
+    file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> Result<Mapping> {
     let mut mapping = Mapping::with_descriptor(message.clone());
-    mapping.index = get_index_options(message)?;
+    mapping.index = get_index_options(message)?.map(Box::new);
+    // `protoc` generates synthetic oneofs for proto3 `optional` fields. Map those fields as usual.
+    let oneofs = message
+        .oneofs()
+        .filter(|oneof| !oneof.is_synthetic())
+        .map(|oneof| {
+            Ok((
+                oneof.clone(),
+                get_oneof_options(&oneof)?.unwrap_or_default(),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
     for field in message.fields() {
-        if let Some((name, property)) = compile_field(ctx, &field, file, diagnostics)? {
-            mapping.properties.insert(name, property);
+        // Compile oneof members with the rest of their group.
+        if field.containing_oneof().is_some_and(|o| !o.is_synthetic()) {
+            continue;
+        }
+        if let Some((name, property)) = compile_field(ctx, &field, file, ancestors, diagnostics)? {
+            mapping.fields.entry(name.clone()).or_default().push(field);
+            mapping.insert(name, property);
         }
     }
+    for (oneof, options) in &oneofs {
+        compile_oneof(
+            ctx,
+            oneof,
+            options,
+            &mut mapping,
+            file,
+            ancestors,
+            diagnostics,
+        )?;
+    }
     Ok(mapping)
 }
 
+/// Compile the members of a oneof into `mapping` according to its `strategy`.
+fn compile_oneof(
+    ctx: &Context,
+    oneof: &OneofDescriptor,
+    options: &proto::Oneof,
+    mapping: &mut Mapping,
+    file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<()> {
+    let mut members = Mapping::default();
+    let mut first = None;
+    for field in oneof.fields() {
+        let Some((name, property)) = compile_field(ctx, &field, file, ancestors, diagnostics)?
+        else {
+            continue;
+        };
+        first.get_or_insert_with(|| field.clone());
+        if let Some(other) = members.properties.get(&name)
+            && other.type_() != property.type_()
+        {
+            diagnostics.push(
+                Diagnostic::warning(DiagnosticKind::ConflictingOneofMember {
+                    message: oneof.containing_message().name().to_string(),
+                    oneof: oneof.name().to_string(),
+                    name: name.clone(),
+                })
+                .at(Location {
+                    file: file.to_string(),
+                    span: Span::from_field(&field),
+                    option_path: Vec::new(),
+                }),
+            );
+        }
+        members.fields.entry(name.clone()).or_default().push(field);
+        members.insert(name, property);
+    }
+    if members.properties.is_empty() {
+        return Ok(());
+    }
+    let name = if options.has_name() {
+        options.name()
+    } else {
+        oneof.name()
+    };
+    match options.strategy() {
+        OneofStrategy::ONEOF_STRATEGY_OBJECT => {
+            let parameters = Parameters::Typed {
+                field_mapping: Box::default(),
+                inferred_type: Some("object".to_string()),
+            };
+            // Record the first member as the field that compiles to the object, so that the
+            // validator reports other fields that map to the same property.
+            mapping
+                .fields
+                .entry(name.to_string())
+                .or_default()
+                .extend(first);
+            mapping.insert(
+                name.to_string(),
+                Property::Object {
+                    parameters,
+                    properties: members,
+                },
+            );
+        }
+        strategy => {
+            if strategy == OneofStrategy::ONEOF_STRATEGY_FLAT {
+                mapping.oneofs.insert(
+                    name.to_string(),
+                    members.properties.keys().cloned().collect(),
+                );
+            }
+            mapping.append(&mut members);
+        }
+    }
+    Ok(())
+}
+
+/// Report an error if `field`, or its multi-field `key`, sets more than one typed property for the
+/// same vendor.
+fn check_vendor_properties(
+    field: &FieldDescriptor,
+    file: &str,
+    key: Option<&str>,
+    properties: &[(&VendorProperty, Map<String, Value>)],
+) -> Option<Diagnostic> {
+    let mut by_vendor: BTreeMap<&str, Vec<&VendorProperty>> = BTreeMap::new();
+    for (property, _) in properties {
+        by_vendor
+            .entry(&property.vendor)
+            .or_default()
+            .push(property);
+    }
+    let (vendor, conflicting) = by_vendor.into_iter().find(|(_, p)| p.len() > 1)?;
+    let location = match key {
+        // Diagnostics for multi-fields point at the parent's `mapping.fields`.
+        Some(_) => field_option_location(field, &["mapping", "fields"], None),
+        None => Location {
+            file: file.to_string(),
+            // Point at the second property.
+            span: vendor_span(field, conflicting[1]).or_else(|| Span::from_field(field)),
+            option_path: Vec::new(),
+        },
+    };
+    let name = match key {
+        Some(key) => format!("{}.{key}", field.name()),
+        None => field.name().to_string(),
+    };
+    Some(
+        Diagnostic::error(DiagnosticKind::ConflictingVendorProperties {
+            message: field.containing_message().name().to_string(),
+            field: name,
+            vendor: vendor.to_string(),
+            types: conflicting.iter().map(|p| p.type_name.clone()).collect(),
+        })
+        .at(location),
+    )
+}
+
+/// Find the span of a typed property within [`proto::Field`], i.e., `vendor.(extension)` or
+/// `mapping.vendor.(extension)`.
+fn vendor_span(field: &FieldDescriptor, property: &VendorProperty) -> Option<Span> {
+    [&["vendor"][..], &["mapping", "vendor"]]
+        .iter()
+        .filter_map(|names| {
+            let mut path = option_field_numbers(proto::Field::descriptor(), names);
+            path.push(property.number as i32);
+            let (span, len) = Span::from_field_option(field, &path)?;
+            Some((len == path.len(), len, span))
+        })
+        // Prefer the path that sets the property, then the closest option.
+        .max_by_key(|(exact, len, _)| (*exact, *len))
+        .map(|(_, _, span)| span)
+}
+
+/// Decode the typed properties of the multi-fields of `options`, and return the parameters of the
+/// properties for the vendor that `ctx` targets, keyed by multi-field.
+///
+/// Returns `None` if a multi-field sets more than one typed property for the same vendor.
+fn vendor_multi_fields(
+    ctx: &Context,
+    field: &FieldDescriptor,
+    file: &str,
+    options: &proto::Field,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Option<Map<String, Value>>> {
+    let mut parameters = Map::new();
+    for (key, multi_field) in &options.mapping.fields {
+        let properties =
+            decode_vendor_properties(multi_field.vendor.as_ref(), ctx.vendor_properties())?;
+        if let Some(diagnostic) = check_vendor_properties(field, file, Some(key), &properties) {
+            diagnostics.push(diagnostic);
+            return Ok(None);
+        }
+        if let Some((_, p)) = properties
+            .into_iter()
+            .find(|(property, _)| ctx.target() == Some(property.vendor.as_str()))
+        {
+            parameters.insert(key.clone(), Value::Object(p));
+        }
+    }
+    Ok(Some(parameters))
+}
+
 /// Compile a field as a [`Property`].
 ///
 /// Returns `(name, property)`.
@@ -70,6 +476,7 @@ fn compile_field(
     ctx: &Context,
     field: &FieldDescriptor,
     file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> Result<Option<(String, Property)>> {
     let Some(options) = get_field_options(field)? else {
@@ -79,13 +486,38 @@ fn compile_field(
     let location = Location {
         file: file.to_string(),
         span: Span::from_field(field),
+        option_path: Vec::new(),
     };
-    let property = match ctx
-        .target()
-        .and_then(|label| options.target.iter().find(|t| t.label() == label))
-    {
-        Some(entry) => match serde_json::from_str::<Value>(entry.json()) {
-            Ok(Value::Object(params)) => Property::Leaf(Parameters::Raw(params)),
+    let mut vendor_properties =
+        decode_vendor_properties(options.vendor.as_ref(), ctx.vendor_properties())?;
+    vendor_properties.extend(decode_vendor_properties(
+        options.mapping.vendor.as_ref(),
+        ctx.vendor_properties(),
+    )?);
+    if let Some(diagnostic) = check_vendor_properties(field, file, None, &vendor_properties) {
+        diagnostics.push(diagnostic);
+        return Ok(None);
+    }
+    let Some(vendor_fields) = vendor_multi_fields(ctx, field, file, &options, diagnostics)? else {
+        return Ok(None);
+    };
+    let target = ctx.target().and_then(|label| {
+        options
+            .target
+            .iter()
+            .enumerate()
+            .find(|(_, t)| t.label() == label)
+    });
+    // Literal target mappings take precedence over typed vendor properties.
+    let vendor_parameters = ctx.target().and_then(|label| {
+        vendor_properties
+            .into_iter()
+            .find(|(property, _)| property.vendor == label)
+            .map(|(_, parameters)| parameters)
+    });
+    let property = match (target, vendor_parameters) {
+        (Some((i, entry)), _) => match serde_json::from_str::<Value>(entry.json()) {
+            Ok(Value::Object(params)) => Some(Property::Leaf(Parameters::Raw(params))),
             Ok(_) => {
                 diagnostics.push(
                     Diagnostic::error(DiagnosticKind::InvalidTargetJsonType {
@@ -93,7 +525,7 @@ fn compile_field(
                         field: field.name().to_string(),
                         label: entry.label().to_string(),
                     })
-                    .at(location.clone()),
+                    .at(field_option_location(field, &["target"], Some(i))),
                 );
                 return Ok(None);
             }
@@ -104,12 +536,13 @@ fn compile_field(
                         field: field.name().to_string(),
                         label: entry.label().to_string(),
                     })
-                    .at(location.clone()),
+                    .at(field_option_location(field, &["target"], Some(i))),
                 );
                 return Ok(None);
             }
         },
-        None if ctx.target().is_some() => {
+        (None, Some(parameters)) => Some(Property::Leaf(Parameters::Raw(parameters))),
+        (None, None) if ctx.target().is_some() => {
             if !options.target.is_empty() {
                 // If the field has any targets defined, emit a warning if the provided label does
                 // not match any known targets.
@@ -119,24 +552,100 @@ fn compile_field(
                         field: field.name().to_string(),
                         label: ctx.target().unwrap().to_string(),
                     })
-                    .at(location.clone()),
+                    .at(field_option_location(field, &["target"], None)),
                 );
             }
             // Always return the default mapping.
-            property(field, &options)
+            property(ctx, field, &options)
         }
-        None => property(field, &options),
+        (None, None) => property(ctx, field, &options),
     };
-    // A mapping type, as in an object or nested field.
-    let mapping = match field.runtime_field_type() {
+    let Some(property) = property else {
+        return Ok(None);
+    };
+    // Typed vendor properties replace the multi-fields they are set on.
+    let property = match property {
+        Property::Leaf(parameters @ Parameters::Typed { .. })
+            if !vendor_fields.is_empty() && !field.is_map() =>
+        {
+            let Value::Object(mut parameters) = serde_json::to_value(&parameters)? else {
+                unreachable!("parameters always serialize to an object");
+            };
+            if let Some(Value::Object(fields)) = parameters.get_mut("fields") {
+                fields.extend(vendor_fields);
+            }
+            Property::Leaf(Parameters::Raw(parameters))
+        }
+        property => property,
+    };
+    if let Some(reason) = check_map_strategy(field, &options) {
+        diagnostics.push(
+            Diagnostic::error(DiagnosticKind::InvalidParameterValue {
+                message: field.containing_message().name().to_string(),
+                field: field.name().to_string(),
+                parameter: "map_strategy".to_string(),
+                reason: reason.to_string(),
+            })
+            .at(field_option_location(field, &["map_strategy"], None)),
+        );
+        return Ok(None);
+    }
+    // Literal target mappings replace the whole map.
+    let compiles_map =
+        field.is_map() && matches!(property, Property::Leaf(Parameters::Typed { .. }));
+    // The message type the plugin would recurse into.
+    let nested = match field.runtime_field_type() {
         RuntimeFieldType::Singular(RuntimeType::Message(desc))
         | RuntimeFieldType::Repeated(RuntimeType::Message(desc)) => Some(desc),
+        RuntimeFieldType::Map(_, RuntimeType::Message(desc))
+            if compiles_map
+                && matches!(
+                    options.map_strategy(),
+                    MapStrategy::MAP_STRATEGY_DYNAMIC | MapStrategy::MAP_STRATEGY_KEY_VALUE
+                ) =>
+        {
+            Some(desc)
+        }
         _ => None,
+    };
+    // Well-known types are leaves.
+    if let Some(desc) = nested.filter(|desc| infer_message_type(desc) == "object") {
+        let kind = if ancestors.contains(&desc) {
+            Some(DiagnosticKind::RecursiveMessage {
+                message: field.containing_message().name().to_string(),
+                field: field.name().to_string(),
+                type_name: desc.full_name().to_string(),
+            })
+        } else if ancestors.len() >= ctx.max_depth() {
+            Some(DiagnosticKind::MaxDepthExceeded {
+                message: field.containing_message().name().to_string(),
+                field: field.name().to_string(),
+                max_depth: ctx.max_depth(),
+            })
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            if options.has_recursion() {
+                return Ok(Some((name.to_string(), stop_recursion(&options))));
+            }
+            diagnostics.push(Diagnostic::error(kind).at(location.clone()));
+            return Ok(None);
+        }
     }
-    .map(|desc| compile_message(ctx, &desc, file, diagnostics))
-    .transpose()?
-    .unwrap_or_default();
-    let property = match (mapping.properties.is_empty(), property) {
+    // A mapping type, as in an object or nested field.
+    let mapping = match field.runtime_field_type() {
+        RuntimeFieldType::Singular(RuntimeType::Message(desc))
+        | RuntimeFieldType::Repeated(RuntimeType::Message(desc)) => {
+            compile_message(ctx, &desc, file, ancestors, diagnostics)?
+        }
+        RuntimeFieldType::Map(..) if compiles_map => {
+            compile_map(ctx, field, &options, file, ancestors, diagnostics)?
+        }
+        _ => Mapping::default(),
+    };
+    let is_empty = mapping.properties.is_empty() && mapping.map_templates.is_empty();
+    let property = match (is_empty, property) {
         (false, Property::Leaf(parameters)) => Property::Object {
             parameters,
             properties: mapping,
@@ -146,26 +655,218 @@ fn compile_field(
     Ok(Some((name.to_string(), property)))
 }
 
+/// Check that the field's `map_strategy` fits its type.
+///
+/// Returns the reason if not.
+fn check_map_strategy(field: &FieldDescriptor, options: &proto::Field) -> Option<&'static str> {
+    if !options.has_map_strategy() {
+        return None;
+    }
+    match (field.runtime_field_type(), options.map_strategy()) {
+        (
+            RuntimeFieldType::Map(..),
+            MapStrategy::MAP_STRATEGY_UNSPECIFIED | MapStrategy::MAP_STRATEGY_KEY_VALUE,
+        ) => None,
+        // These strategies use the keys as field names.
+        (RuntimeFieldType::Map(RuntimeType::String, _), _) => None,
+        (RuntimeFieldType::Map(..), _) => Some("requires string keys"),
+        _ => Some("only applies to map fields"),
+    }
+}
+
+/// Map a field that would recurse according to its `recursion` option.
+fn stop_recursion(options: &proto::Field) -> Property {
+    let mut field_mapping = options.mapping.clone().unwrap_or_default();
+    let inferred_type = match options.recursion() {
+        Recursion::RECURSION_FLATTENED => "flattened",
+        _ => {
+            if !field_mapping.has_enabled() {
+                field_mapping.set_enabled(false);
+            }
+            "object"
+        }
+    };
+    Property::Leaf(Parameters::Typed {
+        field_mapping: Box::new(field_mapping),
+        inferred_type: Some(inferred_type.to_string()),
+    })
+}
+
+/// Compile the entries of a map field according to its `map_strategy`.
+fn compile_map(
+    ctx: &Context,
+    field: &FieldDescriptor,
+    options: &proto::Field,
+    file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Mapping> {
+    let RuntimeFieldType::Map(key, value) = field.runtime_field_type() else {
+        unreachable!("compile_map only compiles map fields")
+    };
+    let mut mapping = Mapping::default();
+    match options.map_strategy() {
+        MapStrategy::MAP_STRATEGY_UNSPECIFIED | MapStrategy::MAP_STRATEGY_FLATTENED => {}
+        MapStrategy::MAP_STRATEGY_DYNAMIC => {
+            mapping.map_templates =
+                match compile_map_value(ctx, &value, file, ancestors, diagnostics)? {
+                    Property::Leaf(parameters) => vec![MapTemplate {
+                        path_match: "*".to_string(),
+                        parameters,
+                    }],
+                    // Elasticsearch maps each entry as an object, so only generate templates for the
+                    // properties of the value.
+                    Property::Object { properties, .. } => flatten_map_templates("*", properties),
+                }
+        }
+        MapStrategy::MAP_STRATEGY_KEY_VALUE => {
+            // Use the synthetic map entry message so diagnostics refer to `key` and `value`.
+            let type_name = field.proto().type_name().trim_start_matches('.');
+            mapping.descriptor = field
+                .containing_message()
+                .nested_messages()
+                .find(|m| m.is_map_entry() && m.full_name() == type_name);
+            mapping.insert(
+                "key".to_string(),
+                Property::Leaf(inferred_parameters(ctx, &key)),
+            );
+            mapping.insert(
+                "value".to_string(),
+                compile_map_value(ctx, &value, file, ancestors, diagnostics)?,
+            );
+        }
+    }
+    Ok(mapping)
+}
+
+/// Compile the value type of a map field as a [`Property`].
+fn compile_map_value(
+    ctx: &Context,
+    value: &RuntimeType,
+    file: &str,
+    ancestors: &mut Vec<MessageDescriptor>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Property> {
+    let parameters = inferred_parameters(ctx, value);
+    let properties = match value {
+        RuntimeType::Message(desc) => compile_message(ctx, desc, file, ancestors, diagnostics)?,
+        _ => Mapping::default(),
+    };
+    if properties.properties.is_empty() && properties.map_templates.is_empty() {
+        Ok(Property::Leaf(parameters))
+    } else {
+        Ok(Property::Object {
+            parameters,
+            properties,
+        })
+    }
+}
+
+/// Generate a dynamic template for every property in `mapping`, relative to `prefix`.
+fn flatten_map_templates(prefix: &str, mapping: Mapping) -> Vec<MapTemplate> {
+    let mut templates = Vec::new();
+    for (name, property) in mapping.properties {
+        let path_match = format!("{prefix}.{name}");
+        match property {
+            Property::Leaf(parameters) => templates.push(MapTemplate {
+                path_match,
+                parameters,
+            }),
+            Property::Object {
+                parameters,
+                properties,
+            } => {
+                templates.push(MapTemplate {
+                    path_match: path_match.clone(),
+                    parameters,
+                });
+                templates.extend(flatten_map_templates(&path_match, properties));
+            }
+        }
+    }
+    templates.extend(
+        mapping
+            .map_templates
+            .into_iter()
+            .map(|template| template.under(prefix)),
+    );
+    templates
+}
+
+/// Build [`Parameters`] with only an inferred `type`.
+fn inferred_parameters(ctx: &Context, t: &RuntimeType) -> Parameters {
+    Parameters::Typed {
+        field_mapping: Box::default(),
+        inferred_type: Some(infer_field_type(ctx, t).to_string()),
+    }
+}
+
 /// Build a [`Property`] from `FieldMapping`, inferring `type` if absent.
-fn property(field: &FieldDescriptor, options: &proto::Field) -> Property {
-    let field_mapping = options.mapping.clone().unwrap_or_default();
+///
+/// Returns `None` if the field does not need a mapping, i.e., it is a `google.protobuf.Empty`.
+fn property(ctx: &Context, field: &FieldDescriptor, options: &proto::Field) -> Option<Property> {
+    let mut field_mapping = options.mapping.clone().unwrap_or_default();
+    let message_type = match field.runtime_field_type() {
+        RuntimeFieldType::Singular(RuntimeType::Message(desc))
+        | RuntimeFieldType::Repeated(RuntimeType::Message(desc)) => {
+            Some(desc.full_name().to_string())
+        }
+        _ => None,
+    };
+    match (message_type.as_deref(), field_mapping.type_()) {
+        (Some("google.protobuf.Empty"), _) if !field_mapping.has_type() => return None,
+        // `Any` holds arbitrary messages, so do not try to index them as an object.
+        (Some("google.protobuf.Any"), "" | "object") if !field_mapping.has_enabled() => {
+            field_mapping.set_enabled(false)
+        }
+        // A `Struct` mapped as an `object` must accept unknown keys.
+        (Some("google.protobuf.Struct"), "object") if !field_mapping.has_dynamic() => {
+            field_mapping.set_dynamic(Dynamic::DYNAMIC_TRUE)
+        }
+        _ => {}
+    }
+    // As must a map mapped with generated dynamic templates.
+    if field.is_map()
+        && options.map_strategy() == MapStrategy::MAP_STRATEGY_DYNAMIC
+        && !field_mapping.has_dynamic()
+    {
+        field_mapping.set_dynamic(Dynamic::DYNAMIC_TRUE)
+    }
     let inferred_type = if field_mapping.has_type() {
         None
     } else {
         Some(match field.runtime_field_type() {
             RuntimeFieldType::Singular(t) | RuntimeFieldType::Repeated(t) => {
-                infer_type(&t).to_string()
+                infer_field_type(ctx, &t).to_string()
             }
-            RuntimeFieldType::Map(_, _) => "object".to_string(),
+            RuntimeFieldType::Map(_, _) => match options.map_strategy() {
+                MapStrategy::MAP_STRATEGY_FLATTENED => "flattened".to_string(),
+                MapStrategy::MAP_STRATEGY_KEY_VALUE => "nested".to_string(),
+                _ => "object".to_string(),
+            },
         })
     };
-    Property::Leaf(Parameters::Typed {
+    Some(Property::Leaf(Parameters::Typed {
         field_mapping: Box::new(field_mapping),
         inferred_type,
-    })
+    }))
 }
 
-fn infer_type(t: &RuntimeType) -> &str {
+/// Infer the mapping type of a field with runtime type `t`, as configured by the plugin parameters.
+fn infer_field_type<'a>(ctx: &Context, t: &'a RuntimeType) -> &'a str {
+    match t {
+        RuntimeType::Message(desc) if desc.full_name() == "google.protobuf.Timestamp" => {
+            ctx.timestamp_type()
+        }
+        RuntimeType::Message(desc) if desc.full_name() == "google.protobuf.Duration" => {
+            ctx.duration_type()
+        }
+        _ => infer_type(t),
+    }
+}
+
+/// Infer the mapping type of a field with runtime type `t`.
+pub fn infer_type(t: &RuntimeType) -> &str {
     match t {
         RuntimeType::I32 => "integer",
         RuntimeType::I64 => "long",
@@ -176,7 +877,30 @@ fn infer_type(t: &RuntimeType) -> &str {
         RuntimeType::Bool => "boolean",
         RuntimeType::String => "keyword",
         RuntimeType::VecU8 => "binary",
-        RuntimeType::Message(_) => "object",
+        RuntimeType::Message(desc) => infer_message_type(desc),
         RuntimeType::Enum(_) => "keyword",
     }
 }
+
+/// Infer the type of a message field.
+///
+/// Well-known types map to the type of their canonical JSON representation. All other messages are
+/// objects.
+fn infer_message_type(desc: &MessageDescriptor) -> &'static str {
+    match desc.full_name() {
+        "google.protobuf.Timestamp" => "date",
+        "google.protobuf.Duration" => "keyword",
+        "google.protobuf.FieldMask" => "keyword",
+        "google.protobuf.Struct" => "flattened",
+        "google.protobuf.BoolValue" => "boolean",
+        "google.protobuf.BytesValue" => "binary",
+        "google.protobuf.DoubleValue" => "double",
+        "google.protobuf.FloatValue" => "float",
+        "google.protobuf.Int32Value" => "integer",
+        "google.protobuf.Int64Value" => "long",
+        "google.protobuf.StringValue" => "keyword",
+        "google.protobuf.UInt32Value" => "long",
+        "google.protobuf.UInt64Value" => "unsigned_long",
+        _ => "object",
+    }
+}
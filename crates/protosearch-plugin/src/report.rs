@@ -0,0 +1,615 @@
+//! Render diagnostics for people and tools.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use crate::Result;
+use crate::config::{Config, DiagnosticsFormat};
+use crate::diagnostic::{Diagnostic, Location, Severity};
+use crate::span::{Span, TAB_WIDTH};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const REFERENCE_URL: &str = "https://github.com/benwebber/protosearch/blob/main/doc/reference.md";
+/// The most lines of a span to quote. Longer spans elide the lines before the last.
+const MAX_SNIPPET_LINES: u32 = 4;
+
+/// The source files diagnostics refer to.
+#[derive(Debug, Default)]
+pub struct Sources(BTreeMap<String, String>);
+
+impl Sources {
+    /// Read the files `diagnostics` refer to from the first of `include_paths` that contains them,
+    /// or the current directory.
+    ///
+    /// Skips files that cannot be read.
+    pub fn load(include_paths: &[PathBuf], diagnostics: &[Diagnostic]) -> Self {
+        let files: BTreeSet<&str> = diagnostics
+            .iter()
+            .flat_map(|d| {
+                d.location
+                    .iter()
+                    .chain(d.related.iter().map(|r| &r.location))
+            })
+            .map(|l| l.file.as_str())
+            .collect();
+        let sources = files
+            .into_iter()
+            .filter_map(|file| {
+                let source = match include_paths {
+                    [] => std::fs::read_to_string(file).ok(),
+                    paths => paths
+                        .iter()
+                        .find_map(|path| std::fs::read_to_string(path.join(file)).ok()),
+                };
+                Some((file.to_string(), source?))
+            })
+            .collect();
+        Self(sources)
+    }
+
+    /// Narrow the locations of `diagnostics` within aggregate option values (`{...}`) to the
+    /// fields they refer to. See [`Span::narrow`].
+    ///
+    /// Leaves a location as is if its file was not loaded or it does not set the field.
+    pub fn resolve(&self, diagnostics: &mut [Diagnostic]) {
+        let locations = diagnostics.iter_mut().flat_map(|d| {
+            d.location
+                .iter_mut()
+                .chain(d.related.iter_mut().map(|r| &mut r.location))
+        });
+        for location in locations.filter(|l| !l.option_path.is_empty()) {
+            let (Some(span), Some(source)) = (location.span, self.0.get(&location.file)) else {
+                continue;
+            };
+            let path: Vec<&str> = location.option_path.iter().map(String::as_str).collect();
+            if let Some(span) = span.narrow(source, &path) {
+                location.span = Some(span);
+                location.option_path.clear();
+            }
+        }
+    }
+
+    /// Return line number `line` of `file`, with tabs expanded to match `protoc`'s columns.
+    fn line(&self, file: &str, line: u32) -> Option<String> {
+        let text = self
+            .0
+            .get(file)?
+            .lines()
+            .nth(line.checked_sub(1)? as usize)?;
+        let mut expanded = String::new();
+        for c in text.chars() {
+            match c {
+                '\t' => {
+                    let width = TAB_WIDTH as usize - expanded.chars().count() % TAB_WIDTH as usize;
+                    expanded.push_str(&" ".repeat(width));
+                }
+                c => expanded.push(c),
+            }
+        }
+        Some(expanded)
+    }
+}
+
+/// Render `diagnostics` in `format`.
+///
+/// Only the [`DiagnosticsFormat::Rich`] format quotes `sources`.
+pub fn render(
+    format: DiagnosticsFormat,
+    diagnostics: &[Diagnostic],
+    sources: &Sources,
+) -> Result<String> {
+    match format {
+        DiagnosticsFormat::Human => Ok(diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")),
+        DiagnosticsFormat::Rich => Ok(diagnostics
+            .iter()
+            .map(|d| rich(d, sources))
+            .collect::<Vec<_>>()
+            .join("\n\n")),
+        DiagnosticsFormat::Json => Ok(serde_json::to_string_pretty(diagnostics)?),
+        DiagnosticsFormat::Sarif => Ok(serde_json::to_string_pretty(&sarif(diagnostics))?),
+    }
+}
+
+/// Return the format to report diagnostics in for people, i.e., `rich` if `format` is `rich` and
+/// `human` otherwise.
+pub fn text_format(format: DiagnosticsFormat) -> DiagnosticsFormat {
+    match format {
+        DiagnosticsFormat::Rich => DiagnosticsFormat::Rich,
+        _ => DiagnosticsFormat::Human,
+    }
+}
+
+/// Render what the plugin prints to standard error, if anything.
+///
+/// `protoc` prints errors, so this only includes them in a JSON or SARIF report without a
+/// `diagnostics_path`. Otherwise, it renders the warnings in the [`text_format`].
+pub fn render_stderr(
+    config: &Config,
+    diagnostics: &[Diagnostic],
+    sources: &Sources,
+) -> Result<Option<String>> {
+    let machine_readable = matches!(
+        config.diagnostics,
+        DiagnosticsFormat::Json | DiagnosticsFormat::Sarif
+    );
+    if machine_readable && config.diagnostics_path.is_none() {
+        return render(config.diagnostics, diagnostics, sources).map(Some);
+    }
+    let warnings: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| !d.is_error())
+        .cloned()
+        .collect();
+    if warnings.is_empty() {
+        return Ok(None);
+    }
+    render(text_format(config.diagnostics), &warnings, sources).map(Some)
+}
+
+/// Render `diagnostic` with the lines it refers to, like `rustc`.
+///
+/// ```text
+/// error[E001]: tests.Article.title: 'ignore_above' must be greater than 0
+///  --> tests/article.proto:4:3
+///   |
+/// 4 |   (protosearch.field).mapping.ignore_above = 0
+///   |   ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+///   |
+///   = help: valid values are 1 to 2147483647
+/// ```
+fn rich(diagnostic: &Diagnostic, sources: &Sources) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    // The gutter fits the largest line number.
+    let width = diagnostic
+        .location
+        .iter()
+        .chain(diagnostic.related.iter().map(|r| &r.location))
+        .filter_map(|l| l.span)
+        .map(|s| s.end.line.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let mut out = format!("{severity}[{}]: {}", diagnostic.code(), diagnostic.kind);
+    if let Some(location) = &diagnostic.location {
+        out.push_str(&snippet(location, sources, '^', width));
+    }
+    for related in &diagnostic.related {
+        out.push_str(&format!("\nnote: {}", related.message));
+        out.push_str(&snippet(&related.location, sources, '-', width));
+    }
+    if let Some(help) = diagnostic.kind.help() {
+        out.push_str(&format!("\n{:width$} |\n{:width$} = help: {help}", "", ""));
+    }
+    out
+}
+
+/// Render the location and, if the source is available, the lines of `location`'s span,
+/// underlined with `marker`.
+///
+/// If the span is longer than [`MAX_SNIPPET_LINES`], this elides the lines before the last.
+fn snippet(location: &Location, sources: &Sources, marker: char, width: usize) -> String {
+    let mut out = format!("\n{:width$}--> {location}", "");
+    let Some(Span { start, end }) = location.span else {
+        return out;
+    };
+    if sources.line(&location.file, start.line).is_none() {
+        return out;
+    }
+    out.push_str(&format!("\n{:width$} |", ""));
+    let elided = end.line - start.line >= MAX_SNIPPET_LINES;
+    for n in start.line..=end.line {
+        if elided && (start.line + MAX_SNIPPET_LINES - 1..end.line).contains(&n) {
+            if n == start.line + MAX_SNIPPET_LINES - 1 {
+                out.push_str("\n...");
+            }
+            continue;
+        }
+        let Some(line) = sources.line(&location.file, n) else {
+            break;
+        };
+        // Underline continuation lines from their first non-blank character.
+        let from = if n == start.line {
+            start.column.saturating_sub(1) as usize
+        } else {
+            line.chars().take_while(|c| c.is_whitespace()).count()
+        };
+        let to = if n == end.line {
+            end.column.saturating_sub(1) as usize
+        } else {
+            line.chars().count()
+        };
+        out.push_str(&format!("\n{n:>width$} | {line}"));
+        if n == start.line || to > from {
+            let underline = marker.to_string().repeat(to.saturating_sub(from).max(1));
+            out.push_str(&format!("\n{:width$} | {:from$}{underline}", "", ""));
+        }
+    }
+    out
+}
+
+/// Build a SARIF log with one run and a rule for each diagnostic code.
+fn sarif(diagnostics: &[Diagnostic]) -> Value {
+    let rules: Vec<Value> = diagnostics
+        .iter()
+        .map(Diagnostic::code)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|code| {
+            json!({
+                "id": code,
+                "helpUri": format!("{REFERENCE_URL}#{}", code.to_lowercase()),
+            })
+        })
+        .collect();
+    let results: Vec<Value> = diagnostics.iter().map(sarif_result).collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "protoc-gen-protosearch",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/benwebber/protosearch",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn sarif_result(diagnostic: &Diagnostic) -> Value {
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    let mut result = json!({
+        "ruleId": diagnostic.code(),
+        "level": level,
+        "message": {"text": diagnostic.kind.to_string()},
+    });
+    if let Some(location) = &diagnostic.location {
+        result["locations"] = json!([sarif_location(location)]);
+    }
+    if !diagnostic.related.is_empty() {
+        result["relatedLocations"] = diagnostic
+            .related
+            .iter()
+            .enumerate()
+            .map(|(i, related)| {
+                let mut location = sarif_location(&related.location);
+                location["id"] = json!(i);
+                location["message"] = json!({"text": related.message});
+                location
+            })
+            .collect();
+    }
+    result
+}
+
+fn sarif_location(location: &Location) -> Value {
+    let mut physical = json!({"artifactLocation": {"uri": location.file}});
+    if let Some(span) = location.span {
+        physical["region"] = json!({
+            "startLine": span.start.line,
+            "startColumn": span.start.column,
+            "endLine": span.end.line,
+            "endColumn": span.end.column,
+        });
+    }
+    json!({"physicalLocation": physical})
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{Sources, render, render_stderr};
+    use crate::config::{Config, DiagnosticsFormat};
+    use crate::diagnostic::{Diagnostic, DiagnosticKind, Location};
+    use crate::span::{Point, Span};
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        let location = |line| Location {
+            file: "example.proto".to_string(),
+            span: Some(Span::new(Point::new(line, 3), Point::new(line, 20))),
+            option_path: Vec::new(),
+        };
+        vec![
+            Diagnostic::error(DiagnosticKind::DuplicateProperty {
+                message: "example.Article".to_string(),
+                field: "title".to_string(),
+                name: "title".to_string(),
+            })
+            .at(location(4))
+            .with_related(location(5), "'headline' maps to 'title' here"),
+            Diagnostic::warning(DiagnosticKind::RoutingNotRequired {
+                message: "example.Article".to_string(),
+            })
+            .at(Location {
+                file: "example.proto".to_string(),
+                span: None,
+                option_path: Vec::new(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_human() {
+        let diagnostics = diagnostics();
+        assert_eq!(
+            render(DiagnosticsFormat::Human, &diagnostics, &Sources::default()).unwrap(),
+            format!("{}\n{}", diagnostics[0], diagnostics[1])
+        );
+    }
+
+    #[test]
+    fn test_rich() {
+        let sources = Sources(
+            [(
+                "example.proto".to_string(),
+                "syntax = \"proto3\";\n\nmessage Article {\n  string title = 1;\n  string headline = 2 [(protosearch.field).name = \"title\"];\n}\n"
+                    .to_string(),
+            )]
+            .into(),
+        );
+        let diagnostics = diagnostics();
+        assert_eq!(
+            render(DiagnosticsFormat::Rich, &diagnostics, &sources).unwrap(),
+            [
+                "error[E007]: example.Article.title: property 'title' is already defined",
+                " --> example.proto:4:3",
+                "  |",
+                "4 |   string title = 1;",
+                "  |   ^^^^^^^^^^^^^^^^^",
+                "note: 'headline' maps to 'title' here",
+                " --> example.proto:5:3",
+                "  |",
+                "5 |   string headline = 2 [(protosearch.field).name = \"title\"];",
+                "  |   -----------------",
+                "  |",
+                "  = help: set 'name' on one of the fields to map it to a different property",
+                "",
+                "warning[W010]: example.Article: '_routing.required' is false, but join fields require routing",
+                " --> example.proto",
+                "  |",
+                "  = help: set '_routing.required' to true",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_rich_tabs() {
+        let sources = Sources(
+            [(
+                "example.proto".to_string(),
+                "\tstring\ttitle = 1;".to_string(),
+            )]
+            .into(),
+        );
+        let diagnostic = Diagnostic::warning(DiagnosticKind::InvalidFieldName {
+            message: "example.Article".to_string(),
+            field: "title".to_string(),
+            name: "Title".to_string(),
+        })
+        .at(Location {
+            file: "example.proto".to_string(),
+            // `protoc` counts a tab as advancing to the next multiple of 8.
+            span: Some(Span::new(Point::new(1, 17), Point::new(1, 22))),
+            option_path: Vec::new(),
+        });
+        let rendered = render(DiagnosticsFormat::Rich, &[diagnostic], &sources).unwrap();
+        assert!(rendered.contains("1 |         string  title = 1;\n  |                 ^^^^^\n"));
+    }
+
+    #[test]
+    fn test_rich_multiline() {
+        let sources = Sources(
+            [(
+                "example.proto".to_string(),
+                (1..=9).map(|n| format!("  line {n}\n")).collect(),
+            )]
+            .into(),
+        );
+        let diagnostic = |end| {
+            Diagnostic::warning(DiagnosticKind::RoutingNotRequired {
+                message: "example.Article".to_string(),
+            })
+            .at(Location {
+                file: "example.proto".to_string(),
+                span: Some(Span::new(Point::new(2, 5), Point::new(end, 7))),
+                option_path: Vec::new(),
+            })
+        };
+        let rendered = render(DiagnosticsFormat::Rich, &[diagnostic(3)], &sources).unwrap();
+        assert!(
+            rendered.contains(
+                &[
+                    "2 |   line 2",
+                    "  |     ^^^^",
+                    "3 |   line 3",
+                    "  |   ^^^^",
+                    "  |",
+                ]
+                .join("\n")
+            )
+        );
+        let rendered = render(DiagnosticsFormat::Rich, &[diagnostic(9)], &sources).unwrap();
+        assert!(
+            rendered.contains(
+                &[
+                    "4 |   line 4",
+                    "  |   ^^^^^^",
+                    "...",
+                    "9 |   line 9",
+                    "  |   ^^^^",
+                    "  |",
+                ]
+                .join("\n")
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_source() {
+        let diagnostics = diagnostics();
+        let rendered = render(
+            DiagnosticsFormat::Rich,
+            &diagnostics[..1],
+            &Sources::default(),
+        )
+        .unwrap();
+        assert!(rendered.starts_with(
+            "error[E007]: example.Article.title: property 'title' is already defined\n --> example.proto:4:3\nnote:"
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let diagnostics = diagnostics();
+        let report: Value = serde_json::from_str(
+            &render(DiagnosticsFormat::Json, &diagnostics, &Sources::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            json!([
+                {
+                    "code": "E007",
+                    "severity": "error",
+                    "message": diagnostics[0].kind.to_string(),
+                    "location": {
+                        "file": "example.proto",
+                        "span": {
+                            "start": {"line": 4, "column": 3},
+                            "end": {"line": 4, "column": 20},
+                        },
+                    },
+                    "related": [{
+                        "location": {
+                            "file": "example.proto",
+                            "span": {
+                                "start": {"line": 5, "column": 3},
+                                "end": {"line": 5, "column": 20},
+                            },
+                        },
+                        "message": "'headline' maps to 'title' here",
+                    }],
+                },
+                {
+                    "code": "W010",
+                    "severity": "warning",
+                    "message": diagnostics[1].kind.to_string(),
+                    "location": {"file": "example.proto", "span": null},
+                    "related": [],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sarif() {
+        let diagnostics = diagnostics();
+        let report: Value = serde_json::from_str(
+            &render(DiagnosticsFormat::Sarif, &diagnostics, &Sources::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(report["version"], "2.1.0");
+        let run = &report["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([
+                {"id": "E007", "helpUri": format!("{}#e007", super::REFERENCE_URL)},
+                {"id": "W010", "helpUri": format!("{}#w010", super::REFERENCE_URL)},
+            ])
+        );
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "E007",
+                "level": "error",
+                "message": {"text": diagnostics[0].kind.to_string()},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": "example.proto"},
+                        "region": {"startLine": 4, "startColumn": 3, "endLine": 4, "endColumn": 20},
+                    },
+                }],
+                "relatedLocations": [{
+                    "id": 0,
+                    "message": {"text": "'headline' maps to 'title' here"},
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": "example.proto"},
+                        "region": {"startLine": 5, "startColumn": 3, "endLine": 5, "endColumn": 20},
+                    },
+                }],
+            })
+        );
+        assert_eq!(
+            run["results"][1]["locations"],
+            json!([{"physicalLocation": {"artifactLocation": {"uri": "example.proto"}}}])
+        );
+    }
+
+    /// Render what the plugin prints to standard error with `parameter`.
+    fn stderr(parameter: &str, diagnostics: &[Diagnostic]) -> Option<String> {
+        let config = Config::try_from(parameter).unwrap();
+        render_stderr(&config, diagnostics, &Sources::default()).unwrap()
+    }
+
+    #[test]
+    fn test_stderr_warnings() {
+        let diagnostics = diagnostics();
+        let warnings =
+            |format| Some(render(format, &diagnostics[1..], &Sources::default()).unwrap());
+        assert_eq!(stderr("", &diagnostics), warnings(DiagnosticsFormat::Human));
+        assert_eq!(
+            stderr("diagnostics=rich", &diagnostics),
+            warnings(DiagnosticsFormat::Rich)
+        );
+        assert_eq!(
+            stderr(
+                "diagnostics=rich,diagnostics_path=protosearch.txt",
+                &diagnostics
+            ),
+            warnings(DiagnosticsFormat::Rich)
+        );
+        assert_eq!(
+            stderr(
+                "diagnostics=sarif,diagnostics_path=protosearch.sarif",
+                &diagnostics
+            ),
+            warnings(DiagnosticsFormat::Human)
+        );
+        assert_eq!(stderr("", &diagnostics[..1]), None);
+    }
+
+    #[test]
+    fn test_stderr_report() {
+        let diagnostics = diagnostics();
+        assert_eq!(
+            stderr("diagnostics=sarif", &diagnostics),
+            Some(render(DiagnosticsFormat::Sarif, &diagnostics, &Sources::default()).unwrap())
+        );
+        assert_eq!(
+            stderr("diagnostics=json", &diagnostics[..1]),
+            Some(
+                render(
+                    DiagnosticsFormat::Json,
+                    &diagnostics[..1],
+                    &Sources::default()
+                )
+                .unwrap()
+            )
+        );
+    }
+}
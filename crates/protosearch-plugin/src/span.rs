@@ -4,10 +4,17 @@
 //! themselves. That file is thoroughly documented. Read it to better understand this module.
 //!
 //! [`descriptor.proto`]: https://github.com/protocolbuffers/protobuf/blob/v34.0/src/google/protobuf/descriptor.proto
-use protobuf::reflect::{FieldDescriptor, MessageDescriptor};
+use protobuf::reflect::{FieldDescriptor, FileDescriptor, MessageDescriptor};
+
+use serde::Serialize;
+
+use crate::options::EXTENSION_NUMBER;
+
+/// `protoc` advances columns to the next multiple of 8 at a tab.
+pub const TAB_WIDTH: u32 = 8;
 
 /// A span of text between two points in a source file.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Span {
     pub start: Point,
     pub end: Point,
@@ -16,7 +23,7 @@ pub struct Span {
 /// A point in a source file.
 ///
 /// `line` and `column` both start from `1`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Point {
     pub line: u32,
     pub column: u32,
@@ -27,22 +34,108 @@ impl Span {
         Self { start, end }
     }
 
+    /// Return whether `other` is within this span.
+    pub fn contains(&self, other: &Span) -> bool {
+        let (start, end) = (
+            (self.start.line, self.start.column),
+            (self.end.line, self.end.column),
+        );
+        start <= (other.start.line, other.start.column) && (other.end.line, other.end.column) <= end
+    }
+
     /// Extract the span of a protobuf field.
     pub fn from_field(field: &FieldDescriptor) -> Option<Self> {
-        const FIELD_NUMBER: i32 = 2;
         let message = field.containing_message();
-        let file_proto = message.file_descriptor_proto();
-        let mut path = message_path(&message)?;
-        let idx = message
-            .fields()
-            .position(|f| f.number() == field.number())?;
-        path.push(FIELD_NUMBER);
-        path.push(idx as i32);
-        let source_code_info = file_proto.source_code_info.as_ref()?;
+        Self::from_path(message.file_descriptor(), &field_path(field)?)
+    }
+
+    /// Extract the span of a protobuf message.
+    pub fn from_message(message: &MessageDescriptor) -> Option<Self> {
+        Self::from_path(message.file_descriptor(), &message_path(message)?)
+    }
+
+    /// Extract the span of a `protosearch` field option, e.g.,
+    /// `[(protosearch.field).mapping.ignore_above = 256]`.
+    ///
+    /// `path` is a sequence of field numbers (and indices of repeated fields) within the option.
+    /// See [`Span::from_message_option`].
+    pub fn from_field_option(field: &FieldDescriptor, path: &[i32]) -> Option<(Self, usize)> {
+        const OPTIONS_NUMBER: i32 = 8;
+        let mut option_path = field_path(field)?;
+        option_path.extend([OPTIONS_NUMBER, EXTENSION_NUMBER as i32]);
+        let prefix_len = option_path.len();
+        option_path.extend(path);
+        let message = field.containing_message();
+        Self::from_option_path(message.file_descriptor(), &option_path, prefix_len)
+    }
+
+    /// Extract the span of a `protosearch` message option, e.g., `option (protosearch.index) = {}`.
+    ///
+    /// `path` is a sequence of field numbers (and indices of repeated fields) within the option.
+    /// `protoc` only records the location of options set individually, e.g.,
+    /// `option (protosearch.index).dynamic = DYNAMIC_STRICT;`, and not the fields of an aggregate
+    /// value (`{...}`). This falls back to the first location under the longest prefix of `path`,
+    /// and returns the length of that prefix with the span. [`Span::narrow`] finds the rest.
+    pub fn from_message_option(message: &MessageDescriptor, path: &[i32]) -> Option<(Self, usize)> {
+        const OPTIONS_NUMBER: i32 = 7;
+        let mut option_path = message_path(message)?;
+        option_path.extend([OPTIONS_NUMBER, EXTENSION_NUMBER as i32]);
+        let prefix_len = option_path.len();
+        option_path.extend(path);
+        Self::from_option_path(message.file_descriptor(), &option_path, prefix_len)
+    }
+
+    fn from_path(file: &FileDescriptor, path: &[i32]) -> Option<Self> {
+        let source_code_info = file.proto().source_code_info.as_ref()?;
         let location = source_code_info.location.iter().find(|l| l.path == path)?;
         Self::from_proto(&location.span)
     }
 
+    /// Find the first location under the longest prefix of `path`, but no shorter than
+    /// `min_len`, i.e., the option itself.
+    ///
+    /// Returns the span and the length of the prefix after `min_len`.
+    fn from_option_path(
+        file: &FileDescriptor,
+        path: &[i32],
+        min_len: usize,
+    ) -> Option<(Self, usize)> {
+        let source_code_info = file.proto().source_code_info.as_ref()?;
+        (min_len..=path.len()).rev().find_map(|len| {
+            let location = source_code_info
+                .location
+                .iter()
+                .find(|l| l.path.starts_with(&path[..len]))?;
+            Some((Self::from_proto(&location.span)?, len - min_len))
+        })
+    }
+
+    /// Narrow the span of an option with an aggregate value (`{...}`) to the field at a `path` of
+    /// field names within the value, e.g., `ignore_above` in
+    /// `(protosearch.field).mapping = {type: "keyword" ignore_above: 256}`.
+    ///
+    /// `protoc` does not record the location of fields within an aggregate value, so this scans
+    /// `source`, the text of the file. Returns `None` if the value does not set the field.
+    pub fn narrow(&self, source: &str, path: &[&str]) -> Option<Self> {
+        let tokens = tokenize(source, self);
+        // Skip the option name.
+        let mut value = tokens.iter().position(|t| t.text == "=")? + 1;
+        let mut span = None;
+        for name in path {
+            if tokens.get(value)?.text != "{" {
+                return None;
+            }
+            let key = find_key(&tokens, value, name)?;
+            value = match tokens.get(key + 1)?.text.as_str() {
+                ":" => key + 2,
+                _ => key + 1,
+            };
+            let end = value_end(&tokens, value)?;
+            span = Some(Span::new(tokens[key].start, tokens[end].end));
+        }
+        span
+    }
+
     /// Convert a protobuf `SourceCodeInfo.Location` span to a `Span`.
     ///
     /// A protobuf span always has three or four elements: start line, start column, end line
@@ -127,3 +220,135 @@ fn message_path(message: &MessageDescriptor) -> Option<Vec<i32>> {
         }
     }
 }
+
+/// Build the `SourceCodeInfo` path to `field`.
+///
+/// In `DescriptorProto`, the field number for fields (`field`) is `2`.
+fn field_path(field: &FieldDescriptor) -> Option<Vec<i32>> {
+    const FIELD_NUMBER: i32 = 2;
+    let message = field.containing_message();
+    let mut path = message_path(&message)?;
+    let idx = message
+        .fields()
+        .position(|f| f.number() == field.number())?;
+    path.push(FIELD_NUMBER);
+    path.push(idx as i32);
+    Some(path)
+}
+
+/// A token in the text of an option value.
+#[derive(Debug)]
+struct Token {
+    text: String,
+    start: Point,
+    end: Point,
+}
+
+/// Split the text of `source` within `span` into tokens, skipping whitespace and comments.
+///
+/// Strings and runs of identifier and number characters are single tokens. Any other character is
+/// a token by itself.
+fn tokenize(source: &str, span: &Span) -> Vec<Token> {
+    let mut chars = Vec::new();
+    let lines = source
+        .lines()
+        .zip(1..)
+        .skip_while(|(_, n)| *n < span.start.line)
+        .take_while(|(_, n)| *n <= span.end.line);
+    for (line, n) in lines {
+        let mut column = 1;
+        for c in line.chars() {
+            let point = Point::new(n, column);
+            column = match c {
+                '\t' => (column - 1) / TAB_WIDTH * TAB_WIDTH + TAB_WIDTH + 1,
+                _ => column + 1,
+            };
+            if span.contains(&Span::new(point, Point::new(n, column))) {
+                chars.push((point, c));
+            }
+        }
+        chars.push((Point::new(n, column), '\n'));
+    }
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+');
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(&(start, c)) = chars.get(i) {
+        let len = match (c, chars.get(i + 1).map(|(_, c)| *c)) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('/', Some('/')) => {
+                i += chars[i..].iter().take_while(|(_, c)| *c != '\n').count();
+                continue;
+            }
+            ('/', Some('*')) => {
+                i += 2 + chars[i + 2..]
+                    .windows(2)
+                    .position(|w| w[0].1 == '*' && w[1].1 == '/')
+                    .map_or(chars.len(), |n| n + 2);
+                continue;
+            }
+            ('"' | '\'', _) => {
+                let mut escaped = false;
+                chars[i + 1..]
+                    .iter()
+                    .position(|&(_, d)| {
+                        let end = d == c && !escaped;
+                        escaped = d == '\\' && !escaped;
+                        end
+                    })
+                    .map_or(chars.len() - i, |n| n + 2)
+            }
+            (c, _) if is_word(c) => chars[i..].iter().take_while(|(_, c)| is_word(*c)).count(),
+            _ => 1,
+        };
+        let (last, _) = chars[i + len - 1];
+        tokens.push(Token {
+            text: chars[i..i + len].iter().map(|(_, c)| c).collect(),
+            start,
+            end: Point::new(last.line, last.column + 1),
+        });
+        i += len;
+    }
+    tokens
+}
+
+/// Find the token of the key `name` in the message value that opens at `tokens[open]`, i.e., an
+/// identifier followed by `:` or `{` outside any nested value.
+fn find_key(tokens: &[Token], open: usize, name: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, pair) in tokens.windows(2).enumerate().skip(open + 1) {
+        match pair[0].text.as_str() {
+            "{" | "[" => depth += 1,
+            "}" | "]" if depth == 0 => return None,
+            "}" | "]" => depth -= 1,
+            key if depth == 0 && key == name && matches!(pair[1].text.as_str(), ":" | "{") => {
+                return Some(i);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Find the last token of the value that starts at `tokens[start]`.
+fn value_end(tokens: &[Token], start: usize) -> Option<usize> {
+    if !matches!(tokens.get(start)?.text.as_str(), "{" | "[") {
+        return Some(start);
+    }
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token.text.as_str() {
+            "{" | "[" => depth += 1,
+            "}" | "]" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
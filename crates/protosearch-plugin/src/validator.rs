@@ -1,25 +1,148 @@
 use std::collections::BTreeMap;
 use std::sync::LazyLock;
 
+use crate::date_format;
 use crate::diagnostic::{Diagnostic, DiagnosticKind, Location};
-use crate::mapping::{Mapping, Parameters, Property};
-use crate::options::{get_field_options, property_name};
-use crate::proto::FieldMapping;
+use crate::mapping::{FIELD_MAPPING_PLUGIN_FIELDS, Mapping, Parameters, Property};
+use crate::options::{get_field_options, option_field_numbers, property_name};
+use crate::parameters;
+use crate::plugin::infer_type;
+use crate::proto::dynamic_template::Template_mapping;
+use crate::proto::{self, FieldMapping, Index, IndexMode, SourceMode};
 use crate::span::Span;
-use protobuf::reflect::MessageDescriptor;
+use protobuf::descriptor::field_descriptor_proto::Type;
+use protobuf::reflect::{
+    FieldDescriptor, MessageDescriptor, ReflectFieldRef, ReflectValueRef, RuntimeFieldType,
+    RuntimeType,
+};
+use protobuf::{EnumFull, MessageDyn, MessageFull};
 use regex::Regex;
 
 static CHECKS: &[&dyn Check] = &[
+    &CopyToCheck,
+    &DuplicatePropertyCheck,
+    &InapplicableParameterCheck,
+    &IncompatibleTypeCheck,
+    &InvalidDateFormatCheck,
+    &InvalidDynamicDateFormatsCheck,
+    &InvalidDynamicTemplateCheck,
     &InvalidNameCheck,
     &InvalidIgnoreAboveCheck,
     &InvalidPositionIncrementGapCheck,
     &InvalidIndexPrefixesCheck,
+    &InvalidMultiFieldCheck,
+    &InvalidNullValueCheck,
+    &InvalidSourceCheck,
+    &RoutingCheck,
+    &ShadowedPropertyCheck,
+    &UnknownAnalyzerCheck,
 ];
 
+/// Conventional field names: lowercase, with digits and underscores, and dots between objects.
+pub const FIELD_NAME_PATTERN: &str = r"^[@a-z][a-z0-9_]*(\.[a-z0-9_]+)*$";
+
+/// Types that hold a string.
+const STRING_TYPES: &[&str] = &[
+    "completion",
+    "constant_keyword",
+    "counted_keyword",
+    "icu_collation_keyword",
+    "keyword",
+    "match_only_text",
+    "search_as_you_type",
+    "semantic_text",
+    "text",
+    "version",
+    "wildcard",
+];
+
+/// Types that hold a number.
+const NUMERIC_TYPES: &[&str] = &[
+    "byte",
+    "double",
+    "float",
+    "half_float",
+    "integer",
+    "long",
+    "scaled_float",
+    "short",
+    "unsigned_long",
+];
+
+/// Types that Elasticsearch coerces from a value of another kind, and which are commonly intended.
+const COERCIONS: &[(ValueKind, &[&str])] = &[
+    (ValueKind::String, &["date", "date_nanos", "ip"]),
+    // Enums serialized as numbers.
+    (ValueKind::Enum, &["byte", "integer", "long", "short"]),
+    // Epoch timestamps and identifiers.
+    (ValueKind::Integer, &["date", "date_nanos", "keyword"]),
+    // Durations serialized as a count of some unit.
+    (ValueKind::Duration, &["long"]),
+];
+
+/// Built-in analyzers.
+///
+/// <https://www.elastic.co/docs/reference/text-analysis/analyzer-reference>
+const BUILTIN_ANALYZERS: &[&str] = &[
+    "fingerprint",
+    "keyword",
+    "pattern",
+    "simple",
+    "standard",
+    "stop",
+    "whitespace",
+    // Language analyzers.
+    "arabic",
+    "armenian",
+    "basque",
+    "bengali",
+    "brazilian",
+    "bulgarian",
+    "catalan",
+    "cjk",
+    "czech",
+    "danish",
+    "dutch",
+    "english",
+    "estonian",
+    "finnish",
+    "french",
+    "galician",
+    "german",
+    "greek",
+    "hindi",
+    "hungarian",
+    "indonesian",
+    "irish",
+    "italian",
+    "latvian",
+    "lithuanian",
+    "norwegian",
+    "persian",
+    "portuguese",
+    "romanian",
+    "russian",
+    "serbian",
+    "sorani",
+    "spanish",
+    "swedish",
+    "thai",
+    "turkish",
+];
+
+/// Built-in normalizers.
+///
+/// <https://www.elastic.co/docs/reference/text-analysis/normalizers>
+const BUILTIN_NORMALIZERS: &[&str] = &["lowercase"];
+
 pub struct ValidationContext<'a> {
     pub file: &'a str,
     pub message: &'a MessageDescriptor,
+    /// Options for the document (top-level message) under validation.
+    pub index: Option<&'a Index>,
     proto_names: BTreeMap<String, String>,
+    /// The field that declares the multi-fields under validation, if any.
+    parent: Option<String>,
 }
 
 impl<'a> ValidationContext<'a> {
@@ -36,10 +159,40 @@ impl<'a> ValidationContext<'a> {
         Self {
             file,
             message,
+            index: None,
             proto_names,
+            parent: None,
         }
     }
 
+    pub fn with_index(self, index: Option<&'a Index>) -> Self {
+        Self { index, ..self }
+    }
+
+    /// Create a context for a nested message in the same document.
+    pub fn nested(&self, message: &'a MessageDescriptor) -> Self {
+        Self::new(self.file, message).with_index(self.index)
+    }
+
+    /// Create a context for the multi-fields of the field `parent`.
+    ///
+    /// Diagnostics name the subfield as `parent.key` and point at `parent`.
+    pub fn multi_fields<'b>(&self, parent: &str, keys: impl Iterator<Item = &'b String>) -> Self {
+        Self {
+            file: self.file,
+            message: self.message,
+            index: self.index,
+            proto_names: keys
+                .map(|key| (key.clone(), format!("{parent}.{key}")))
+                .collect(),
+            parent: Some(parent.to_string()),
+        }
+    }
+
+    pub fn is_multi_field(&self) -> bool {
+        self.parent.is_some()
+    }
+
     pub fn proto_name<'b>(&'b self, mapping_name: &'b str) -> &'b str {
         self.proto_names
             .get(mapping_name)
@@ -49,7 +202,7 @@ impl<'a> ValidationContext<'a> {
 
     pub fn field_span(&self, proto_name: &str) -> Option<Span> {
         self.message
-            .field_by_name(proto_name)
+            .field_by_name(self.parent.as_deref().unwrap_or(proto_name))
             .and_then(|f| Span::from_field(&f))
     }
 
@@ -57,22 +210,99 @@ impl<'a> ValidationContext<'a> {
         Location {
             file: self.file.to_string(),
             span: self.field_span(proto_name),
+            option_path: Vec::new(),
+        }
+    }
+
+    pub fn message_location(&self) -> Location {
+        Location {
+            file: self.file.to_string(),
+            span: Span::from_message(self.message),
+            option_path: Vec::new(),
+        }
+    }
+
+    /// The location of an index option at a `path` of [`Index`] field names, e.g.,
+    /// `["_source", "mode"]`.
+    ///
+    /// Falls back to the location of the message.
+    pub fn index_location(&self, path: &[&str]) -> Location {
+        self.index_option_location(path, None)
+    }
+
+    /// The location of the item at `index` of the repeated index option at `path`.
+    pub fn index_item_location(&self, path: &[&str], index: usize) -> Location {
+        self.index_option_location(path, Some(index))
+    }
+
+    fn index_option_location(&self, path: &[&str], index: Option<usize>) -> Location {
+        let mut numbers = option_field_numbers(Index::descriptor(), path);
+        numbers.extend(index.map(|i| i as i32));
+        match Span::from_message_option(self.message, &numbers) {
+            Some((span, len)) => Location {
+                file: self.file.to_string(),
+                span: Some(span),
+                option_path: unresolved(path, len),
+            },
+            None => self.message_location(),
+        }
+    }
+
+    /// The location of the `(protosearch.field)` option of the field `proto_name` at a `path` of
+    /// [`proto::Field`](crate::proto::Field) field names, e.g., `["mapping", "ignore_above"]`.
+    ///
+    /// Diagnostics for multi-fields point at the parent's `mapping.fields`.
+    pub fn option_location(&self, proto_name: &str, path: &[&str]) -> Location {
+        let path = match self.parent {
+            Some(_) => &["mapping", "fields"],
+            None => path,
+        };
+        match self
+            .message
+            .field_by_name(self.parent.as_deref().unwrap_or(proto_name))
+        {
+            Some(field) => field_option_location(&field, path, None),
+            None => self.location(proto_name),
         }
     }
 }
 
 pub trait Check: Sync {
+    /// Check a single property. Runs for every property in the document.
     fn check_property(
         &self,
-        ctx: &ValidationContext<'_>,
-        name: &str,
-        property: &Property,
-        diagnostics: &mut Vec<Diagnostic>,
-    );
+        _ctx: &ValidationContext<'_>,
+        _name: &str,
+        _property: &Property,
+        _diagnostics: &mut Vec<Diagnostic>,
+    ) {
+    }
+
+    /// Check the properties of an object. Runs for the document and every object property.
+    fn check_object(
+        &self,
+        _ctx: &ValidationContext<'_>,
+        _mapping: &Mapping,
+        _diagnostics: &mut Vec<Diagnostic>,
+    ) {
+    }
+
+    /// Check the document as a whole. Runs once for the top-level message.
+    fn check_mapping(
+        &self,
+        _ctx: &ValidationContext<'_>,
+        _mapping: &Mapping,
+        _diagnostics: &mut Vec<Diagnostic>,
+    ) {
+    }
 }
 
 pub fn validate(ctx: &ValidationContext<'_>, mapping: &Mapping) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
+    for check in CHECKS {
+        check.check_mapping(ctx, mapping, &mut diagnostics);
+        check.check_object(ctx, mapping, &mut diagnostics);
+    }
     for (name, property) in &mapping.properties {
         walk(ctx, name, property, &mut diagnostics);
     }
@@ -88,20 +318,514 @@ fn walk(
     for check in CHECKS {
         check.check_property(ctx, name, property, diagnostics);
     }
+    if let Some(field_mapping) = field_mapping(property)
+        && !field_mapping.fields.is_empty()
+    {
+        let mut keys: Vec<&String> = field_mapping.fields.keys().collect();
+        keys.sort();
+        let multi_field_ctx = ctx.multi_fields(ctx.proto_name(name), keys.iter().copied());
+        for key in keys {
+            let subfield = Property::Leaf(Parameters::Typed {
+                field_mapping: Box::new(field_mapping.fields[key].clone()),
+                inferred_type: None,
+            });
+            for check in CHECKS {
+                check.check_property(&multi_field_ctx, key, &subfield, diagnostics);
+            }
+        }
+    }
     if let Property::Object { properties, .. } = property {
         let nested_ctx;
         let ctx = if let Some(desc) = &properties.descriptor {
-            nested_ctx = ValidationContext::new(ctx.file, desc);
+            nested_ctx = ctx.nested(desc);
             &nested_ctx
         } else {
             ctx
         };
+        for check in CHECKS {
+            check.check_object(ctx, properties, diagnostics);
+        }
         for (name, prop) in &properties.properties {
             walk(ctx, name, prop, diagnostics);
         }
     }
 }
 
+struct CopyToCheck;
+
+impl Check for CopyToCheck {
+    fn check_mapping(
+        &self,
+        _ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        check_copy_to(mapping, mapping, "", "", diagnostics);
+    }
+}
+
+/// Check the `copy_to` targets of the properties of `mapping`, the object at `prefix`.
+///
+/// `scope` is the path of the innermost `nested` field that contains the object, or empty.
+fn check_copy_to(
+    root: &Mapping,
+    mapping: &Mapping,
+    prefix: &str,
+    scope: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (name, property) in &mapping.properties {
+        if let (Some(field_mapping), Some(field)) = (
+            field_mapping(property),
+            mapping.fields.get(name).and_then(|fields| fields.last()),
+        ) {
+            for (i, path) in field_mapping.copy_to.iter().enumerate() {
+                let diagnostic = match (root.get(path), nested_scope(root, path)) {
+                    (Some(target), Some(target_scope)) => {
+                        let reason = if matches!(target, Property::Object { .. })
+                            || matches!(target.type_(), Some("object" | "nested"))
+                        {
+                            format!("cannot copy to object field '{path}'")
+                        } else if !target_scope.is_empty()
+                            && scope != target_scope
+                            && !scope.starts_with(&format!("{target_scope}."))
+                        {
+                            // Elasticsearch only copies to the same nested document or its parents.
+                            format!("cannot copy into nested field '{target_scope}'")
+                        } else {
+                            continue;
+                        };
+                        Diagnostic::error(DiagnosticKind::InvalidParameterValue {
+                            message: field.containing_message().full_name().to_string(),
+                            field: field.name().to_string(),
+                            parameter: "copy_to".to_string(),
+                            reason,
+                        })
+                    }
+                    _ => Diagnostic::warning(DiagnosticKind::UnknownCopyToTarget {
+                        message: field.containing_message().full_name().to_string(),
+                        field: field.name().to_string(),
+                        path: path.to_string(),
+                    }),
+                };
+                diagnostics.push(diagnostic.at(field_option_location(
+                    field,
+                    &["mapping", "copy_to"],
+                    Some(i),
+                )));
+            }
+        }
+        if let Property::Object { properties, .. } = property {
+            let path = match prefix {
+                "" => name.to_string(),
+                _ => format!("{prefix}.{name}"),
+            };
+            let scope = if property.type_() == Some("nested") {
+                path.as_str()
+            } else {
+                scope
+            };
+            check_copy_to(root, properties, &path, scope, diagnostics);
+        }
+    }
+}
+
+/// Find the path of the innermost `nested` field that contains the property at `path`, or an
+/// empty string if it is not in a `nested` field.
+fn nested_scope(mapping: &Mapping, path: &str) -> Option<String> {
+    if mapping.properties.contains_key(path) {
+        return Some(String::new());
+    }
+    path.match_indices('.')
+        .find_map(|(i, _)| match mapping.properties.get(&path[..i]) {
+            Some(property @ Property::Object { properties, .. }) => {
+                nested_scope(properties, &path[i + 1..]).map(|inner| {
+                    if !inner.is_empty() {
+                        format!("{}.{inner}", &path[..i])
+                    } else if property.type_() == Some("nested") {
+                        path[..i].to_string()
+                    } else {
+                        inner
+                    }
+                })
+            }
+            _ => None,
+        })
+}
+
+struct DuplicatePropertyCheck;
+
+impl Check for DuplicatePropertyCheck {
+    fn check_object(
+        &self,
+        ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (name, fields) in &mapping.fields {
+            for (i, field) in fields.iter().enumerate().skip(1) {
+                // Members of the same oneof never appear together, so they may share a property.
+                if let Some(other) = fields[..i].iter().find(|other| !same_oneof(field, other)) {
+                    diagnostics.push(duplicate_property(ctx, name, field, other));
+                }
+            }
+        }
+        // A dotted name such as `a.b` collides with the property `b` of an object `a`.
+        for name in mapping.properties.keys().filter(|name| name.contains('.')) {
+            let other = name.match_indices('.').find_map(|(i, _)| {
+                match mapping.properties.get(&name[..i]) {
+                    Some(Property::Object { properties, .. }) => properties.field(&name[i + 1..]),
+                    _ => None,
+                }
+            });
+            if let Some(other) = other
+                && let Some(field) = mapping.fields.get(name).and_then(|fields| fields.first())
+            {
+                diagnostics.push(duplicate_property(ctx, name, field, other));
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` are members of the same oneof.
+///
+/// The plugin compiles the members of a oneof together and reports members that map to the same
+/// name with different types as [`DiagnosticKind::ConflictingOneofMember`]. Synthetic oneofs only
+/// ever hold one field.
+fn same_oneof(a: &FieldDescriptor, b: &FieldDescriptor) -> bool {
+    match (a.containing_oneof(), b.containing_oneof()) {
+        (Some(a), Some(b)) => !a.is_synthetic() && a.full_name() == b.full_name(),
+        _ => false,
+    }
+}
+
+fn duplicate_property(
+    ctx: &ValidationContext<'_>,
+    name: &str,
+    field: &FieldDescriptor,
+    other: &FieldDescriptor,
+) -> Diagnostic {
+    Diagnostic::error(DiagnosticKind::DuplicateProperty {
+        message: ctx.message.full_name().to_string(),
+        field: field.name().to_string(),
+        name: name.to_string(),
+    })
+    .at(field_location(field))
+    .with_related(
+        field_location(other),
+        format!("'{}' also maps to this property", other.full_name()),
+    )
+}
+
+/// The location of the `(protosearch.field)` option of `field` at a `path` of field names and,
+/// for repeated options, the `index` of an item.
+///
+/// Falls back to the location of the field.
+pub fn field_option_location(
+    field: &FieldDescriptor,
+    path: &[&str],
+    index: Option<usize>,
+) -> Location {
+    let mut numbers = option_field_numbers(proto::Field::descriptor(), path);
+    numbers.extend(index.map(|i| i as i32));
+    let location = field_location(field);
+    match Span::from_field_option(field, &numbers) {
+        Some((span, len)) => Location {
+            span: Some(span),
+            option_path: unresolved(path, len),
+            ..location
+        },
+        None => location,
+    }
+}
+
+/// The field names in `path` after the first `len`, i.e., those without a location.
+fn unresolved(path: &[&str], len: usize) -> Vec<String> {
+    path.iter().skip(len).map(ToString::to_string).collect()
+}
+
+/// The location of `field` in the file that declares it.
+fn field_location(field: &FieldDescriptor) -> Location {
+    Location {
+        file: field
+            .containing_message()
+            .file_descriptor()
+            .name()
+            .to_string(),
+        span: Span::from_field(field),
+        option_path: Vec::new(),
+    }
+}
+
+struct InapplicableParameterCheck;
+
+impl Check for InapplicableParameterCheck {
+    fn check_property(
+        &self,
+        ctx: &ValidationContext<'_>,
+        name: &str,
+        property: &Property,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let proto_name = ctx.proto_name(name);
+        let (Some(field_mapping), Some(type_name)) = (field_mapping(property), property.type_())
+        else {
+            return;
+        };
+        for parameter in set_parameters(field_mapping) {
+            if parameter == "type"
+                || FIELD_MAPPING_PLUGIN_FIELDS.contains(&parameter.as_str())
+                || parameters::applies(type_name, &parameter) != Some(false)
+            {
+                continue;
+            }
+            let location = ctx.option_location(proto_name, &["mapping", &parameter]);
+            diagnostics.push(
+                Diagnostic::warning(DiagnosticKind::InapplicableParameter {
+                    message: ctx.message.full_name().to_string(),
+                    field: proto_name.to_string(),
+                    parameter,
+                    type_name: type_name.to_string(),
+                })
+                .at(location),
+            );
+        }
+    }
+}
+
+/// The names of the parameters set in `field_mapping`.
+fn set_parameters(field_mapping: &FieldMapping) -> Vec<String> {
+    let message = field_mapping as &dyn MessageDyn;
+    message
+        .descriptor_dyn()
+        .fields()
+        .filter(|field| match field.get_reflect(message) {
+            // Like the serializer, treat UNSPECIFIED enum values as unset.
+            ReflectFieldRef::Optional(v) => {
+                !matches!(v.value(), None | Some(ReflectValueRef::Enum(_, 0)))
+            }
+            ReflectFieldRef::Repeated(v) => !v.is_empty(),
+            ReflectFieldRef::Map(m) => !m.is_empty(),
+        })
+        .map(|field| field.name().to_string())
+        .collect()
+}
+
+struct IncompatibleTypeCheck;
+
+impl Check for IncompatibleTypeCheck {
+    fn check_object(
+        &self,
+        ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (name, fields) in &mapping.fields {
+            // The last member of a oneof defines the property.
+            let (Some(field), Some(type_name)) = (
+                fields.last(),
+                mapping.properties.get(name).and_then(Property::type_),
+            ) else {
+                continue;
+            };
+            let (RuntimeFieldType::Singular(t) | RuntimeFieldType::Repeated(t)) =
+                field.runtime_field_type()
+            else {
+                continue;
+            };
+            let Some(kind) = value_kind(&t) else {
+                continue;
+            };
+            if is_compatible(kind, type_name) != Some(false) {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::warning(DiagnosticKind::IncompatibleType {
+                    message: ctx.message.full_name().to_string(),
+                    field: field.name().to_string(),
+                    proto_type: proto_type_name(field),
+                    type_name: type_name.to_string(),
+                    suggestion: infer_type(&t).to_string(),
+                })
+                .at(field_option_location(field, &["mapping", "type"], None)),
+            );
+        }
+    }
+}
+
+/// The kind of JSON value that represents a protobuf value.
+#[derive(Clone, Copy, PartialEq)]
+enum ValueKind {
+    Bool,
+    Bytes,
+    Duration,
+    Enum,
+    Integer,
+    Number,
+    String,
+}
+
+fn value_kind(t: &RuntimeType) -> Option<ValueKind> {
+    match t {
+        RuntimeType::I32 | RuntimeType::I64 | RuntimeType::U32 | RuntimeType::U64 => {
+            Some(ValueKind::Integer)
+        }
+        RuntimeType::F32 | RuntimeType::F64 => Some(ValueKind::Number),
+        RuntimeType::Bool => Some(ValueKind::Bool),
+        RuntimeType::String => Some(ValueKind::String),
+        RuntimeType::Enum(_) => Some(ValueKind::Enum),
+        RuntimeType::VecU8 => Some(ValueKind::Bytes),
+        RuntimeType::Message(desc) => match desc.full_name() {
+            "google.protobuf.Duration" => Some(ValueKind::Duration),
+            "google.protobuf.Timestamp"
+            | "google.protobuf.FieldMask"
+            | "google.protobuf.StringValue" => Some(ValueKind::String),
+            "google.protobuf.BoolValue" => Some(ValueKind::Bool),
+            "google.protobuf.BytesValue" => Some(ValueKind::Bytes),
+            "google.protobuf.DoubleValue" | "google.protobuf.FloatValue" => Some(ValueKind::Number),
+            "google.protobuf.Int32Value"
+            | "google.protobuf.Int64Value"
+            | "google.protobuf.UInt32Value"
+            | "google.protobuf.UInt64Value" => Some(ValueKind::Integer),
+            _ => None,
+        },
+    }
+}
+
+/// Return whether a value of `kind` can be indexed as `type_name`, or `None` if `type_name` does not
+/// hold a single scalar value (e.g. `object` or `geo_point`).
+fn is_compatible(kind: ValueKind, type_name: &str) -> Option<bool> {
+    let natural: &[&str] = match kind {
+        ValueKind::Bool => &["boolean"],
+        ValueKind::Bytes => &["binary"],
+        ValueKind::Integer | ValueKind::Number => NUMERIC_TYPES,
+        ValueKind::Duration | ValueKind::Enum | ValueKind::String => STRING_TYPES,
+    };
+    let scalar = STRING_TYPES.contains(&type_name)
+        || NUMERIC_TYPES.contains(&type_name)
+        || matches!(
+            type_name,
+            "binary" | "boolean" | "date" | "date_nanos" | "ip"
+        );
+    if !scalar {
+        return None;
+    }
+    let coerced = COERCIONS
+        .iter()
+        .any(|(k, types)| *k == kind && types.contains(&type_name));
+    Some(natural.contains(&type_name) || coerced)
+}
+
+/// The protobuf type of `field` as written in a `.proto` file, e.g. `int64`.
+fn proto_type_name(field: &FieldDescriptor) -> String {
+    let proto = field.proto();
+    match proto.type_() {
+        Type::TYPE_MESSAGE | Type::TYPE_ENUM | Type::TYPE_GROUP => {
+            proto.type_name().trim_start_matches('.').to_string()
+        }
+        t => t
+            .descriptor()
+            .name()
+            .trim_start_matches("TYPE_")
+            .to_lowercase(),
+    }
+}
+
+struct InvalidDateFormatCheck;
+
+impl Check for InvalidDateFormatCheck {
+    fn check_property(
+        &self,
+        ctx: &ValidationContext<'_>,
+        name: &str,
+        property: &Property,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let proto_name = ctx.proto_name(name);
+        let Some(field_mapping) = field_mapping(property) else {
+            return;
+        };
+        if !field_mapping.has_format() {
+            return;
+        }
+        if let Err(reason) = date_format::validate(field_mapping.format()) {
+            diagnostics.push(
+                Diagnostic::error(DiagnosticKind::InvalidParameterValue {
+                    message: ctx.message.full_name().to_string(),
+                    field: proto_name.to_string(),
+                    parameter: "format".to_string(),
+                    reason,
+                })
+                .at(ctx.option_location(proto_name, &["mapping", "format"])),
+            );
+        }
+    }
+}
+
+struct InvalidDynamicDateFormatsCheck;
+
+impl Check for InvalidDynamicDateFormatsCheck {
+    fn check_mapping(
+        &self,
+        ctx: &ValidationContext<'_>,
+        _mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(index) = ctx.index else {
+            return;
+        };
+        for (i, format) in index.dynamic_date_formats.iter().enumerate() {
+            if let Err(reason) = date_format::validate(format) {
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticKind::InvalidIndexOption {
+                        message: ctx.message.full_name().to_string(),
+                        option: "dynamic_date_formats".to_string(),
+                        reason,
+                    })
+                    .at(ctx.index_item_location(&["dynamic_date_formats"], i)),
+                );
+            }
+        }
+    }
+}
+
+struct InvalidDynamicTemplateCheck;
+
+impl Check for InvalidDynamicTemplateCheck {
+    fn check_mapping(
+        &self,
+        ctx: &ValidationContext<'_>,
+        _mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(index) = ctx.index else {
+            return;
+        };
+        for template in &index.dynamic_templates {
+            let reason = match &template.template_mapping {
+                _ if !template.has_name() || template.name().is_empty() => Some("must have a name"),
+                None => Some("must have a mapping"),
+                Some(Template_mapping::Json(json)) => match serde_json::from_str(json) {
+                    Ok(serde_json::Value::Object(_)) => None,
+                    Ok(_) => Some("'json' must be a JSON object"),
+                    Err(_) => Some("'json' is not valid JSON"),
+                },
+                Some(_) => None,
+            };
+            if let Some(reason) = reason {
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticKind::InvalidDynamicTemplate {
+                        message: ctx.message.full_name().to_string(),
+                        name: template.name().to_string(),
+                        reason: reason.to_string(),
+                    })
+                    .at(ctx.index_location(&["dynamic_templates"])),
+                );
+            }
+        }
+    }
+}
+
 struct InvalidNameCheck;
 
 impl Check for InvalidNameCheck {
@@ -113,16 +837,21 @@ impl Check for InvalidNameCheck {
         diagnostics: &mut Vec<Diagnostic>,
     ) {
         let proto_name = ctx.proto_name(name);
-        static RE: LazyLock<Regex> =
-            LazyLock::new(|| Regex::new(r"^[@a-z][a-z0-9_]*(\.[a-z0-9_]+)*$").unwrap());
+        static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(FIELD_NAME_PATTERN).unwrap());
         if !RE.is_match(name) {
+            // Point at the `name` option if the field was renamed.
+            let location = if name == proto_name {
+                ctx.location(proto_name)
+            } else {
+                ctx.option_location(proto_name, &["name"])
+            };
             diagnostics.push(
                 Diagnostic::warning(DiagnosticKind::InvalidFieldName {
                     message: ctx.message.full_name().to_string(),
                     field: proto_name.to_string(),
                     name: name.to_string(),
                 })
-                .at(ctx.location(proto_name)),
+                .at(location),
             );
         }
     }
@@ -150,7 +879,7 @@ impl Check for InvalidIgnoreAboveCheck {
                     parameter: "ignore_above".to_string(),
                     reason: "must be greater than 0".to_string(),
                 })
-                .at(ctx.location(proto_name)),
+                .at(ctx.option_location(proto_name, &["mapping", "ignore_above"])),
             );
         }
     }
@@ -179,7 +908,7 @@ impl Check for InvalidPositionIncrementGapCheck {
                     parameter: "position_increment_gap".to_string(),
                     reason: "must be greater than or equal to 0".to_string(),
                 })
-                .at(ctx.location(proto_name)),
+                .at(ctx.option_location(proto_name, &["mapping", "position_increment_gap"])),
             );
         }
     }
@@ -210,7 +939,7 @@ impl Check for InvalidIndexPrefixesCheck {
                     parameter: "index_prefixes.min_chars".to_string(),
                     reason: "must be greater than or equal to 0".to_string(),
                 })
-                .at(ctx.location(proto_name)),
+                .at(ctx.option_location(proto_name, &["mapping", "index_prefixes", "min_chars"])),
             );
         }
         if prefixes.has_max_chars() && !(0..=20).contains(&prefixes.max_chars()) {
@@ -221,7 +950,328 @@ impl Check for InvalidIndexPrefixesCheck {
                     parameter: "index_prefixes.max_chars".to_string(),
                     reason: "must be less than or equal to 20".to_string(),
                 })
-                .at(ctx.location(proto_name)),
+                .at(ctx.option_location(proto_name, &["mapping", "index_prefixes", "max_chars"])),
+            );
+        }
+    }
+}
+
+struct InvalidMultiFieldCheck;
+
+impl Check for InvalidMultiFieldCheck {
+    fn check_property(
+        &self,
+        ctx: &ValidationContext<'_>,
+        name: &str,
+        property: &Property,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if !ctx.is_multi_field() {
+            return;
+        }
+        let proto_name = ctx.proto_name(name);
+        let Some(field_mapping) = field_mapping(property) else {
+            return;
+        };
+        let mut reasons = Vec::new();
+        if name.contains('.') {
+            reasons.push("name cannot contain '.'".to_string());
+        }
+        match property.type_() {
+            None => reasons.push("must have a 'type'".to_string()),
+            Some(t @ ("object" | "nested")) => reasons.push(format!("cannot be '{t}'")),
+            Some(_) => {}
+        }
+        if !field_mapping.fields.is_empty() {
+            reasons.push("cannot have 'fields'".to_string());
+        }
+        if !field_mapping.copy_to.is_empty() {
+            reasons.push("cannot have 'copy_to'".to_string());
+        }
+        for reason in reasons {
+            diagnostics.push(
+                Diagnostic::error(DiagnosticKind::InvalidMultiField {
+                    message: ctx.message.full_name().to_string(),
+                    field: proto_name.to_string(),
+                    reason,
+                })
+                .at(ctx.option_location(proto_name, &[])),
+            );
+        }
+    }
+}
+
+struct InvalidNullValueCheck;
+
+impl Check for InvalidNullValueCheck {
+    fn check_property(
+        &self,
+        ctx: &ValidationContext<'_>,
+        name: &str,
+        property: &Property,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let proto_name = ctx.proto_name(name);
+        let (Some(field_mapping), Some(type_name)) = (field_mapping(property), property.type_())
+        else {
+            return;
+        };
+        let Some(value) = field_mapping.null_value.as_ref() else {
+            return;
+        };
+        // `None` if the type does not support `null_value`.
+        let (valid, expected) = match type_name {
+            "text" | "match_only_text" => (false, None),
+            "boolean" => (value.has_bool_value(), Some("a bool")),
+            // Dates also accept epoch timestamps, depending on `format`.
+            "date" | "date_nanos" => (
+                value.has_string_value() || value.has_number_value(),
+                Some("a string or number"),
+            ),
+            "icu_collation_keyword" | "ip" | "keyword" | "wildcard" => {
+                (value.has_string_value(), Some("a string"))
+            }
+            t if NUMERIC_TYPES.contains(&t) || t == "token_count" => {
+                (value.has_number_value(), Some("a number"))
+            }
+            _ => return,
+        };
+        if valid || value.has_null_value() {
+            return;
+        }
+        let reason = match expected {
+            Some(expected) => format!("must be {expected} for '{type_name}' fields"),
+            None => format!("is not supported by '{type_name}' fields"),
+        };
+        diagnostics.push(
+            Diagnostic::error(DiagnosticKind::InvalidParameterValue {
+                message: ctx.message.full_name().to_string(),
+                field: proto_name.to_string(),
+                parameter: "null_value".to_string(),
+                reason,
+            })
+            .at(ctx.option_location(proto_name, &["mapping", "null_value"])),
+        );
+    }
+}
+
+struct InvalidSourceCheck;
+
+impl Check for InvalidSourceCheck {
+    fn check_mapping(
+        &self,
+        ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(source) = ctx.index.and_then(|i| i._source.as_ref()) else {
+            return;
+        };
+        // Elasticsearch rejects mappings that set both, even if they agree.
+        if source.mode() != SourceMode::SOURCE_MODE_UNSPECIFIED && source.has_enabled() {
+            diagnostics.push(
+                Diagnostic::error(DiagnosticKind::InvalidIndexOption {
+                    message: ctx.message.full_name().to_string(),
+                    option: "_source.mode".to_string(),
+                    reason: "cannot be set with '_source.enabled'".to_string(),
+                })
+                .at(ctx.index_location(&["_source", "mode"])),
+            );
+        }
+        let mut paths = Vec::new();
+        property_paths(mapping, "", &mut paths);
+        for (option, patterns) in [
+            ("includes", &source.includes),
+            ("excludes", &source.excludes),
+        ] {
+            for (i, pattern) in patterns.iter().enumerate() {
+                if !paths.iter().any(|path| path_matches(pattern, path)) {
+                    diagnostics.push(
+                        Diagnostic::warning(DiagnosticKind::UnknownSourcePath {
+                            message: ctx.message.full_name().to_string(),
+                            option: format!("_source.{option}"),
+                            path: pattern.to_string(),
+                        })
+                        .at(ctx.index_item_location(&["_source", option], i)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Collect the dotted paths of every property in `mapping`, including objects.
+fn property_paths(mapping: &Mapping, prefix: &str, paths: &mut Vec<String>) {
+    for (name, property) in &mapping.properties {
+        let path = match prefix {
+            "" => name.to_string(),
+            _ => format!("{prefix}.{name}"),
+        };
+        if let Property::Object { properties, .. } = property {
+            property_paths(properties, &path, paths);
+        }
+        paths.push(path);
+    }
+}
+
+/// Return whether a `_source` `pattern`, which may contain `*` wildcards, matches `path`.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == path;
+    }
+    let pattern = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    Regex::new(&format!("^{pattern}$")).is_ok_and(|re| re.is_match(path))
+}
+
+struct RoutingCheck;
+
+impl Check for RoutingCheck {
+    fn check_mapping(
+        &self,
+        ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(index) = ctx.index else {
+            return;
+        };
+        let routing = index._routing.as_ref();
+        let required = routing.is_some_and(|r| r.required());
+        // Elasticsearch indexes join field children on the same shard as their parent.
+        if routing.is_some_and(|r| r.has_required() && !r.required()) && has_join(mapping) {
+            diagnostics.push(
+                Diagnostic::warning(DiagnosticKind::RoutingNotRequired {
+                    message: ctx.message.full_name().to_string(),
+                })
+                .at(ctx.index_location(&["_routing", "required"])),
+            );
+        }
+        let Some(settings) = index.settings.as_ref() else {
+            return;
+        };
+        // Both vendors only partition documents with a custom routing value.
+        if settings.routing_partition_size() > 1 && !required {
+            diagnostics.push(
+                Diagnostic::error(DiagnosticKind::InvalidIndexOption {
+                    message: ctx.message.full_name().to_string(),
+                    option: "settings.routing_partition_size".to_string(),
+                    reason: "requires '_routing.required: true'".to_string(),
+                })
+                .at(ctx.index_location(&["settings", "routing_partition_size"])),
+            );
+        }
+        // Elasticsearch routes time series documents by their dimensions.
+        if settings.mode() == IndexMode::INDEX_MODE_TIME_SERIES && required {
+            diagnostics.push(
+                Diagnostic::error(DiagnosticKind::InvalidIndexOption {
+                    message: ctx.message.full_name().to_string(),
+                    option: "_routing.required".to_string(),
+                    reason: "cannot be true in a time series index".to_string(),
+                })
+                .at(ctx.index_location(&["_routing", "required"])),
+            );
+        }
+    }
+}
+
+fn has_join(mapping: &Mapping) -> bool {
+    mapping.properties.values().any(|property| match property {
+        Property::Object { properties, .. } => has_join(properties),
+        Property::Leaf(_) => property.type_() == Some("join"),
+    })
+}
+
+struct ShadowedPropertyCheck;
+
+impl Check for ShadowedPropertyCheck {
+    fn check_mapping(
+        &self,
+        ctx: &ValidationContext<'_>,
+        mapping: &Mapping,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(index) = ctx.index else {
+            return;
+        };
+        let mut names: Vec<&String> = index.runtime.keys().collect();
+        names.sort();
+        for name in names {
+            if mapping.get(name).is_some() {
+                diagnostics.push(
+                    Diagnostic::warning(DiagnosticKind::ShadowedProperty {
+                        message: ctx.message.full_name().to_string(),
+                        name: name.to_string(),
+                    })
+                    .at(ctx.index_location(&["runtime"])),
+                );
+            }
+        }
+    }
+}
+
+struct UnknownAnalyzerCheck;
+
+impl Check for UnknownAnalyzerCheck {
+    fn check_property(
+        &self,
+        ctx: &ValidationContext<'_>,
+        name: &str,
+        property: &Property,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let proto_name = ctx.proto_name(name);
+        let Some(field_mapping) = field_mapping(property) else {
+            return;
+        };
+        let analysis = ctx
+            .index
+            .and_then(|i| i.settings.as_ref())
+            .and_then(|s| s.analysis.as_ref());
+        let analyzers: Vec<&str> = analysis
+            .map(|a| a.analyzer.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        let normalizers: Vec<&str> = analysis
+            .map(|a| a.normalizer.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        let references = [
+            (
+                "analyzer",
+                field_mapping.analyzer.as_deref(),
+                BUILTIN_ANALYZERS,
+                &analyzers,
+            ),
+            (
+                "search_analyzer",
+                field_mapping.search_analyzer.as_deref(),
+                BUILTIN_ANALYZERS,
+                &analyzers,
+            ),
+            (
+                "normalizer",
+                field_mapping.normalizer.as_deref(),
+                BUILTIN_NORMALIZERS,
+                &normalizers,
+            ),
+        ];
+        for (parameter, value, builtin, declared) in references {
+            let Some(value) = value else {
+                continue;
+            };
+            if builtin.contains(&value) || declared.contains(&value) {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::warning(DiagnosticKind::UnknownAnalyzer {
+                    message: ctx.message.full_name().to_string(),
+                    field: proto_name.to_string(),
+                    parameter: parameter.to_string(),
+                    name: value.to_string(),
+                })
+                .at(ctx.option_location(proto_name, &["mapping", parameter])),
             );
         }
     }
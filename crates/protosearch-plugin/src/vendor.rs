@@ -0,0 +1,117 @@
+//! Typed vendor properties.
+//!
+//! `protosearch-vendor` generates a library for each vendor, e.g., `protosearch.elasticsearch`,
+//! that extends [`proto::MappingOptions`] with a typed message for each property type:
+//!
+//! ```protobuf
+//! string title = 1 [(protosearch.field).vendor.(protosearch.elasticsearch.keyword).ignore_above = 256];
+//! ```
+//!
+//! `protobuf` 3.x does not support an extension registry, so the plugin finds the extensions in
+//! the files in the request and decodes them from the unknown fields of
+//! [`proto::MappingOptions`].
+use std::collections::BTreeMap;
+
+use protobuf::UnknownValueRef;
+use protobuf::reflect::{FileDescriptor, MessageDescriptor, RuntimeFieldType, RuntimeType};
+use serde_json::{Map, Value};
+
+use crate::mapping::{number_to_json, to_json};
+use crate::{Result, proto};
+
+/// Extension numbers below this are reserved for `protosearch` itself.
+const FIRST_EXTENSION_NUMBER: u32 = 1000;
+
+/// A typed property extension, e.g., `protosearch.elasticsearch.keyword`.
+#[derive(Debug, Clone)]
+pub struct VendorProperty {
+    /// The last component of the package, e.g., `elasticsearch`.
+    pub vendor: String,
+    /// The property type, i.e., the name of the extension, e.g., `keyword`.
+    pub type_name: String,
+    pub number: u32,
+    descriptor: MessageDescriptor,
+}
+
+/// Find the typed property extensions of [`proto::MappingOptions`] declared in `files`, keyed by number.
+pub fn find_vendor_properties<'a>(
+    files: impl IntoIterator<Item = &'a FileDescriptor>,
+) -> BTreeMap<u32, VendorProperty> {
+    files
+        .into_iter()
+        .flat_map(|file| {
+            let vendor = file.package().rsplit('.').next().unwrap_or_default();
+            file.extensions().filter_map(move |extension| {
+                if extension.containing_message().full_name() != "protosearch.MappingOptions" {
+                    return None;
+                }
+                let RuntimeFieldType::Singular(RuntimeType::Message(descriptor)) =
+                    extension.runtime_field_type()
+                else {
+                    return None;
+                };
+                let number = extension.number() as u32;
+                let property = VendorProperty {
+                    vendor: vendor.to_string(),
+                    type_name: extension.name().to_string(),
+                    number,
+                    descriptor,
+                };
+                Some((number, property))
+            })
+        })
+        .collect()
+}
+
+/// Decode the typed properties set in `vendor`, i.e., `Field.vendor` or `FieldMapping.vendor`, as
+/// mapping parameters, including `type`.
+///
+/// Ignores extensions that are not declared in `properties`.
+pub fn decode_vendor_properties<'a>(
+    vendor: Option<&proto::MappingOptions>,
+    properties: &'a BTreeMap<u32, VendorProperty>,
+) -> Result<Vec<(&'a VendorProperty, Map<String, Value>)>> {
+    let Some(vendor) = vendor else {
+        return Ok(Vec::new());
+    };
+    // An extension set more than once, e.g., one option at a time, is a concatenation.
+    let mut payloads: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    for (number, value) in vendor.special_fields.unknown_fields().iter() {
+        if number >= FIRST_EXTENSION_NUMBER
+            && let UnknownValueRef::LengthDelimited(bytes) = value
+        {
+            payloads.entry(number).or_default().extend_from_slice(bytes);
+        }
+    }
+    payloads
+        .into_iter()
+        .filter_map(|(number, bytes)| Some((properties.get(&number)?, bytes)))
+        .map(|(property, bytes)| {
+            let message = property.descriptor.parse_from_bytes(&bytes)?;
+            let mut parameters = match to_json(&*message)? {
+                Value::Object(parameters) => parameters,
+                _ => Map::new(),
+            };
+            parameters.values_mut().for_each(integral_numbers);
+            parameters.insert(
+                "type".to_string(),
+                Value::String(property.type_name.clone()),
+            );
+            Ok((property, parameters))
+        })
+        .collect()
+}
+
+/// Write the integral numbers in `value` without a fraction.
+///
+/// Vendor libraries declare integer parameters such as `ignore_above` as `double`.
+fn integral_numbers(value: &mut Value) {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            *value = number_to_json(n.as_f64().unwrap_or_default());
+        }
+        Value::Array(values) => values.iter_mut().for_each(integral_numbers),
+        Value::Object(map) => map.values_mut().for_each(integral_numbers),
+        _ => {}
+    }
+}
@@ -30,6 +30,12 @@ pub enum Command {
         #[arg(default_value = "-")]
         output: FileOrStdout,
     },
+    Parameters {
+        #[arg(default_value = "-")]
+        input: FileOrStdin,
+        #[arg(default_value = "-")]
+        output: FileOrStdout,
+    },
     Render {
         #[arg(default_value = "-")]
         input: FileOrStdin,
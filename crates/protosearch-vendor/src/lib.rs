@@ -15,24 +15,9 @@ pub fn extract(openapi: &openapiv3::OpenAPI) -> Result<spec::MappingSpec> {
         .components
         .as_ref()
         .ok_or(Error::InvalidSpec("missing components".into()))?;
-    let property_schema = components
-        .schemas
-        .get("_types.mapping.Property")
-        .ok_or(Error::InvalidSpec(
-            "missing _types.mapping.Property schema".into(),
-        ))?
-        .as_item()
-        .ok_or(Error::InvalidSpec(
-            "_types.mapping.Property is not an item".into(),
-        ))?;
-    let discriminator = &property_schema
-        .schema_data
-        .discriminator
-        .as_ref()
-        .ok_or(Error::InvalidSpec("missing discriminator".into()))?
-        .mapping;
+    let property_schema = openapi::property_schema(components)?;
     let mut types: HashMap<String, spec::PropertyType> = HashMap::new();
-    for (schema_name, typ) in openapi::iter_discriminator_types(discriminator) {
+    for (schema_name, typ) in openapi::discriminator_types(components, property_schema)? {
         let mut parameters = HashMap::new();
         let schema = openapi::resolve(components, schema_name)?;
         openapi::collect_parameters_into(components, schema, &mut parameters)?;
@@ -40,8 +25,9 @@ pub fn extract(openapi: &openapiv3::OpenAPI) -> Result<spec::MappingSpec> {
         types.insert(
             schema_name.to_string(),
             spec::PropertyType {
-                name: typ.to_string(),
+                name: typ,
                 parameters,
+                docs: openapi::docs(&schema.schema_data),
             },
         );
     }
@@ -103,6 +89,8 @@ pub fn compile_into(
                 proto::message_name(ref_name).into(),
             )),
             number: 0,
+            comment: proto::comment(&property.docs),
+            deprecated: property.docs.deprecated,
         })
         .collect();
     fields.sort_by(|a, b| a.name.cmp(&b.name));
@@ -138,8 +126,10 @@ pub fn compile_into(
             .into_iter()
             .map(|(k, v)| proto::Field {
                 name: k.into(),
-                typ: v.clone().into(),
+                typ: v.typ.clone().into(),
                 number: 0,
+                comment: proto::comment(&v.docs),
+                deprecated: v.docs.deprecated,
             })
             .collect();
         let new_message = proto::Message {
@@ -164,6 +154,18 @@ pub fn compile_into(
     Ok(())
 }
 
+/// Tabulate the parameters each property type in a [`Spec`](spec::Spec) supports.
+pub fn parameters(spec: &spec::MappingSpec) -> spec::ParameterTable {
+    let mut table = spec::ParameterTable::new();
+    for property in spec.types.values() {
+        table
+            .entry(property.name.clone())
+            .or_default()
+            .extend(property.parameters.keys().cloned());
+    }
+    table
+}
+
 /// Write a [`File`](proto::File) to a writer.
 pub fn render(w: &mut impl Write, file: &proto::File) -> Result<()> {
     Ok(write!(w, "{}", file)?)
@@ -199,6 +201,13 @@ mod tests {
                     });
                 }
 
+                #[test]
+                fn parameters() {
+                    let openapi = load_openapi();
+                    let spec = crate::extract(&openapi).unwrap();
+                    insta::assert_json_snapshot!(crate::parameters(&spec));
+                }
+
                 #[test]
                 fn render() {
                     let openapi = load_openapi();
@@ -219,4 +228,12 @@ mod tests {
         "protosearch.elasticsearch",
         1000
     );
+
+    // An excerpt of the OpenSearch specification that covers its schema layout.
+    snapshot_tests!(
+        opensearch_fixture,
+        "spec/tests/opensearch.json",
+        "protosearch.opensearch",
+        2000
+    );
 }
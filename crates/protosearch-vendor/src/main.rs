@@ -36,6 +36,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let mut writer = output.clone().into_writer()?;
             serde_json::to_writer_pretty(&mut writer, &spec)?;
         }
+        cli::Command::Parameters { input, output } => {
+            let reader = input.clone().into_reader()?;
+            let spec: spec::MappingSpec = serde_json::from_reader(reader)?;
+            let table = protosearch_vendor::parameters(&spec);
+            let mut writer = output.clone().into_writer()?;
+            serde_json::to_writer_pretty(&mut writer, &table)?;
+        }
         cli::Command::Render { input, output } => {
             let reader = input.clone().into_reader()?;
             let file: proto::File = serde_json::from_reader(reader)?;
@@ -4,8 +4,9 @@ use std::collections::HashMap;
 use indexmap::IndexMap;
 use openapiv3::{
     AdditionalProperties, ArrayType, Components, IntegerFormat, NumberFormat, ReferenceOr, Schema,
-    SchemaKind, Type, VariantOrUnknownOrEmpty,
+    SchemaData, SchemaKind, Type, VariantOrUnknownOrEmpty,
 };
+use serde_json::Value;
 
 use crate::error::{Error, Result};
 use crate::spec;
@@ -33,7 +34,12 @@ pub fn collect_parameters_into(
     match &schema.schema_kind {
         SchemaKind::Type(Type::Object(obj)) => {
             for (name, prop) in &obj.properties {
-                parameters.insert(name.clone(), parameter_from_ref(components, prop)?);
+                let docs = match prop {
+                    ReferenceOr::Item(schema) => docs(&schema.schema_data),
+                    ReferenceOr::Reference { .. } => spec::Docs::default(),
+                };
+                let typ = parameter_type_from_ref(components, prop)?;
+                parameters.insert(name.clone(), spec::Parameter { typ, docs });
             }
         }
         SchemaKind::AllOf { all_of } => {
@@ -50,22 +56,46 @@ pub fn collect_parameters_into(
     Ok(())
 }
 
-fn parameter_from_ref(
+/// Extract the documentation of a schema.
+///
+/// Elasticsearch describes availability with `x-state`, and OpenSearch with `x-version-added` and
+/// `x-version-deprecated`.
+pub fn docs(schema_data: &SchemaData) -> spec::Docs {
+    let extension = |name: &str| schema_data.extensions.get(name).and_then(Value::as_str);
+    let availability = extension("x-state")
+        .map(str::to_string)
+        .or_else(|| extension("x-version-added").map(|version| format!("Added in {version}")));
+    spec::Docs {
+        description: schema_data
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        availability,
+        deprecated: schema_data.deprecated || extension("x-version-deprecated").is_some(),
+    }
+}
+
+fn parameter_type_from_ref(
     components: &Components,
     reference: &ReferenceOr<Box<Schema>>,
-) -> Result<spec::Parameter> {
+) -> Result<spec::ParameterType> {
     match reference {
         ReferenceOr::Reference { reference } => {
             let _schema = resolve(components, reference)?;
-            Ok(spec::Parameter::Optional(value_type_from_ref(
+            Ok(spec::ParameterType::Optional(value_type_from_ref(
                 components, reference,
             )?))
         }
-        ReferenceOr::Item(schema) => parameter_from_schema(components, schema),
+        ReferenceOr::Item(schema) => parameter_type_from_schema(components, schema),
     }
 }
 
-fn parameter_from_schema(components: &Components, schema: &Schema) -> Result<spec::Parameter> {
+fn parameter_type_from_schema(
+    components: &Components,
+    schema: &Schema,
+) -> Result<spec::ParameterType> {
     match &schema.schema_kind {
         SchemaKind::Type(Type::Array(arr)) => {
             let item_type = match &arr.items {
@@ -75,10 +105,10 @@ fn parameter_from_schema(components: &Components, schema: &Schema) -> Result<spe
                 Some(ReferenceOr::Item(s)) => value_type_from_schema(components, s)?,
                 None => return Err(Error::InvalidSpec("array schema has no items".into())),
             };
-            Ok(spec::Parameter::Repeated(item_type))
+            Ok(spec::ParameterType::Repeated(item_type))
         }
         SchemaKind::Type(Type::Object(_)) if is_metadata_object(components, schema)? => {
-            Ok(spec::Parameter::Map(
+            Ok(spec::ParameterType::Map(
                 spec::ScalarType::String,
                 spec::ValueType::Scalar(spec::ScalarType::String),
             ))
@@ -88,14 +118,14 @@ fn parameter_from_schema(components: &Components, schema: &Schema) -> Result<spe
                 if let ReferenceOr::Item(s) = item
                     && matches!(&s.schema_kind, SchemaKind::Type(Type::Array(_)))
                 {
-                    return parameter_from_schema(components, s);
+                    return parameter_type_from_schema(components, s);
                 }
             }
-            Ok(spec::Parameter::Optional(value_type_from_schema(
+            Ok(spec::ParameterType::Optional(value_type_from_schema(
                 components, schema,
             )?))
         }
-        _ => Ok(spec::Parameter::Optional(value_type_from_schema(
+        _ => Ok(spec::ParameterType::Optional(value_type_from_schema(
             components, schema,
         )?)),
     }
@@ -181,6 +211,101 @@ pub fn schema_name(reference: &str) -> &str {
         .unwrap_or(reference)
 }
 
+/// The names of the mapping property schema in each supported specification.
+const PROPERTY_SCHEMAS: [&str; 2] = [
+    // Elasticsearch
+    "_types.mapping.Property",
+    // OpenSearch
+    "_common.mapping___Property",
+];
+
+/// Find the mapping property schema, i.e., the union of all property types.
+pub fn property_schema(components: &Components) -> Result<&Schema> {
+    let name = PROPERTY_SCHEMAS
+        .into_iter()
+        .find(|name| components.schemas.contains_key(*name))
+        .ok_or(Error::InvalidSpec(format!(
+            "missing property schema: expected one of {}",
+            PROPERTY_SCHEMAS.join(", ")
+        )))?;
+    resolve(components, name)
+}
+
+/// List the types of a schema with a discriminator as `(schema name, type name)` pairs.
+///
+/// Elasticsearch maps each type name to a schema in the discriminator. OpenSearch only lists the
+/// schemas in `oneOf`, and each schema restricts the discriminator property to a single value.
+pub fn discriminator_types<'a>(
+    components: &'a Components,
+    schema: &'a Schema,
+) -> Result<Vec<(&'a str, String)>> {
+    let discriminator = schema
+        .schema_data
+        .discriminator
+        .as_ref()
+        .ok_or(Error::InvalidSpec("missing discriminator".into()))?;
+    if !discriminator.mapping.is_empty() {
+        return Ok(iter_discriminator_types(&discriminator.mapping)
+            .map(|(schema_name, typ)| (schema_name, typ.to_string()))
+            .collect());
+    }
+    let SchemaKind::OneOf { one_of } = &schema.schema_kind else {
+        return Err(Error::InvalidSpec(
+            "discriminator has no mapping or oneOf".into(),
+        ));
+    };
+    let mut types = Vec::new();
+    for item in one_of {
+        let ReferenceOr::Reference { reference } = item else {
+            continue;
+        };
+        let variant = resolve(components, reference)?;
+        if let Some(typ) = discriminator_value(components, variant, &discriminator.property_name)?
+            && !typ.starts_with('{')
+        {
+            types.push((schema_name(reference), typ));
+        }
+    }
+    Ok(types)
+}
+
+/// Find the single value a schema allows for the discriminator property `name`.
+///
+/// Recursively follows `allOf` subschemas.
+fn discriminator_value(
+    components: &Components,
+    schema: &Schema,
+    name: &str,
+) -> Result<Option<String>> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => {
+            let Some(ReferenceOr::Item(prop)) = obj.properties.get(name) else {
+                return Ok(None);
+            };
+            match &prop.schema_kind {
+                SchemaKind::Type(Type::String(s)) => match s.enumeration.as_slice() {
+                    [Some(value)] => Ok(Some(value.clone())),
+                    _ => Ok(None),
+                },
+                _ => Ok(None),
+            }
+        }
+        SchemaKind::AllOf { all_of } => {
+            for item in all_of {
+                let s = match item {
+                    ReferenceOr::Reference { reference } => resolve(components, reference)?,
+                    ReferenceOr::Item(s) => s,
+                };
+                if let Some(value) = discriminator_value(components, s, name)? {
+                    return Ok(Some(value));
+                }
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
 pub fn iter_discriminator_types(
     discriminator: &IndexMap<String, String>,
 ) -> impl Iterator<Item = (&str, &str)> {
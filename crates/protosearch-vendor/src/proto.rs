@@ -47,6 +47,11 @@ pub struct Field {
     pub name: String,
     pub typ: FieldType,
     pub number: u32,
+    /// A leading comment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
 }
 
 impl File {
@@ -84,13 +89,24 @@ impl fmt::Display for ValueType {
 
 impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields are always declared in a block, so indent the lines after the comment.
+        for line in self.comment.iter().flat_map(|c| c.lines()) {
+            match line.trim_end() {
+                "" => write!(f, "//\n  ")?,
+                line => write!(f, "// {}\n  ", line)?,
+            }
+        }
         match &self.typ {
-            FieldType::Optional(t) => write!(f, "optional {} {} = {}", t, self.name, self.number),
-            FieldType::Repeated(t) => write!(f, "repeated {} {} = {}", t, self.name, self.number),
+            FieldType::Optional(t) => write!(f, "optional {} {} = {}", t, self.name, self.number)?,
+            FieldType::Repeated(t) => write!(f, "repeated {} {} = {}", t, self.name, self.number)?,
             FieldType::Map(kt, vt) => {
-                write!(f, "map<{}, {}> {} = {}", kt, vt, self.name, self.number)
+                write!(f, "map<{}, {}> {} = {}", kt, vt, self.name, self.number)?
             }
         }
+        if self.deprecated {
+            write!(f, " [deprecated = true]")?;
+        }
+        Ok(())
     }
 }
 
@@ -172,12 +188,12 @@ impl From<spec::ScalarType> for ScalarType {
     }
 }
 
-impl From<spec::Parameter> for FieldType {
-    fn from(p: spec::Parameter) -> Self {
+impl From<spec::ParameterType> for FieldType {
+    fn from(p: spec::ParameterType) -> Self {
         match p {
-            spec::Parameter::Optional(v) => FieldType::Optional(v.into()),
-            spec::Parameter::Repeated(v) => FieldType::Repeated(v.into()),
-            spec::Parameter::Map(kt, vt) => FieldType::Map(kt.into(), vt.into()),
+            spec::ParameterType::Optional(v) => FieldType::Optional(v.into()),
+            spec::ParameterType::Repeated(v) => FieldType::Repeated(v.into()),
+            spec::ParameterType::Map(kt, vt) => FieldType::Map(kt.into(), vt.into()),
         }
     }
 }
@@ -203,15 +219,13 @@ fn merge_fields(
                 return Err(Error::FieldConflict(field.name.clone()));
             }
             new_fields.push(Field {
-                name: field.name.clone(),
-                typ: field.typ.clone(),
                 number: current_number,
+                ..field.clone()
             });
         } else {
             new_fields.push(Field {
-                name: field.name.clone(),
-                typ: field.typ.clone(),
                 number: *next_number,
+                ..field.clone()
             });
             *next_number += 1;
         }
@@ -292,7 +306,22 @@ impl File {
     }
 }
 
+/// Generate a leading comment from the documentation of a property type or parameter.
+pub fn comment(docs: &spec::Docs) -> Option<String> {
+    let paragraphs: Vec<&str> = docs
+        .description
+        .iter()
+        .chain(&docs.availability)
+        .map(String::as_str)
+        .collect();
+    (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n"))
+}
+
 /// Generate a message name from an OpenAPI schema name.
+///
+/// Elasticsearch separates the namespace with `.`, e.g., `_types.mapping.KeywordProperty`, and
+/// OpenSearch with `___`, e.g., `_common.mapping___KeywordProperty`.
 pub fn message_name(schema_name: &str) -> &str {
-    schema_name.rsplit('.').next().unwrap_or(schema_name)
+    let name = schema_name.rsplit("___").next().unwrap_or(schema_name);
+    name.rsplit('.').next().unwrap_or(name)
 }
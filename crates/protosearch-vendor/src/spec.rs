@@ -1,5 +1,5 @@
 //! Mapping specification.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +15,8 @@ pub struct MappingSpec {
 pub struct PropertyType {
     pub name: String,
     pub parameters: HashMap<String, Parameter>,
+    #[serde(flatten)]
+    pub docs: Docs,
 }
 
 /// A named, structured property such as `fielddata`.
@@ -23,14 +25,38 @@ pub struct SharedType {
     pub parameters: HashMap<String, Parameter>,
 }
 
+/// The names of the parameters each property type supports, keyed by type name.
+pub type ParameterTable = BTreeMap<String, BTreeSet<String>>;
+
 /// A mapping parameter.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum Parameter {
+pub struct Parameter {
+    #[serde(flatten)]
+    pub typ: ParameterType,
+    #[serde(flatten)]
+    pub docs: Docs,
+}
+
+/// A mapping parameter type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParameterType {
     Optional(ValueType),
     Repeated(ValueType),
     Map(ScalarType, ValueType),
 }
 
+/// Documentation for a property type or parameter.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Docs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The availability, e.g., `Generally available; Added in 8.0.0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+}
+
 /// A parameter value type.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValueType {
@@ -55,10 +81,10 @@ pub enum ScalarType {
 
 impl Parameter {
     pub fn definition_name(&self) -> Option<&str> {
-        match self {
-            Self::Optional(ValueType::Definition(t))
-            | Self::Repeated(ValueType::Definition(t))
-            | Self::Map(_, ValueType::Definition(t)) => Some(t.as_str()),
+        match &self.typ {
+            ParameterType::Optional(ValueType::Definition(t))
+            | ParameterType::Repeated(ValueType::Definition(t))
+            | ParameterType::Map(_, ValueType::Definition(t)) => Some(t.as_str()),
             _ => None,
         }
     }